@@ -0,0 +1,102 @@
+//! Compares testing every laser against every target with testing it against the targets that
+//! are close to it in a grid. Run with `cargo bench --bench collision`.
+
+use bevy::{math::Vec2, math::Vec3, sprite::collide_aabb::collide};
+use bevy_spaceshooter::{
+    collision::SpatialGrid,
+    constant::{ARENA_HEIGHT, ARENA_WIDTH, COLLISION_GRID_CELL_SIZE},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+const LASER_SIZE: (f32, f32) = (5., 5.);
+const TARGET_SIZE: (f32, f32) = (16., 16.);
+const TARGET_COUNT: usize = 200;
+const LASER_COUNTS: [usize; 3] = [1_000, 5_000, 20_000];
+const ROUNDS: u32 = 20;
+
+fn random_translations(rng: &mut StdRng, count: usize) -> Vec<Vec3> {
+    (0..count)
+        .map(|_| {
+            Vec3::new(
+                rng.gen_range(-ARENA_WIDTH / 2., ARENA_WIDTH / 2.),
+                rng.gen_range(-ARENA_HEIGHT / 2., ARENA_HEIGHT / 2.),
+                0.,
+            )
+        })
+        .collect()
+}
+
+fn brute_force(lasers: &[Vec3], targets: &[Vec3]) -> usize {
+    let mut hits = 0;
+    for laser in lasers.iter() {
+        for target in targets.iter() {
+            if collide(*laser, LASER_SIZE.into(), *target, TARGET_SIZE.into()).is_some() {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn with_grid(grid: &mut SpatialGrid<Vec3>, lasers: &[Vec3], targets: &[Vec3]) -> usize {
+    grid.clear();
+    for target in targets.iter() {
+        grid.insert(
+            *target,
+            Vec2::new(target.x(), target.y()),
+            TARGET_SIZE.into(),
+        );
+    }
+
+    let mut hits = 0;
+    let mut found = Vec::new();
+    for laser in lasers.iter() {
+        grid.query(
+            Vec2::new(laser.x(), laser.y()),
+            LASER_SIZE.into(),
+            &mut found,
+        );
+        for target in found.iter() {
+            if collide(*laser, LASER_SIZE.into(), *target, TARGET_SIZE.into()).is_some() {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+/// Average time of a round, and the hits of the last round so the work is not optimized away
+fn time(mut round: impl FnMut() -> usize) -> (Duration, usize) {
+    let mut hits = round();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        hits = round();
+    }
+    (start.elapsed() / ROUNDS, hits)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let targets = random_translations(&mut rng, TARGET_COUNT);
+    let mut spatial_grid = SpatialGrid::new(
+        Vec2::zero(),
+        Vec2::new(ARENA_WIDTH, ARENA_HEIGHT),
+        COLLISION_GRID_CELL_SIZE,
+    );
+
+    println!("{} targets", TARGET_COUNT);
+    for laser_count in LASER_COUNTS.iter() {
+        let lasers = random_translations(&mut rng, *laser_count);
+        let (brute_force_time, brute_force_hits) = time(|| brute_force(&lasers, &targets));
+        let (grid_time, grid_hits) = time(|| with_grid(&mut spatial_grid, &lasers, &targets));
+        assert_eq!(brute_force_hits, grid_hits, "The grid missed some hits");
+        println!(
+            "{:>6} lasers: every pair {:>10.3?}, grid {:>10.3?} ({:.1}x faster)",
+            laser_count,
+            brute_force_time,
+            grid_time,
+            brute_force_time.as_secs_f64() / grid_time.as_secs_f64()
+        );
+    }
+}
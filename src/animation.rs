@@ -0,0 +1,243 @@
+use crate::{
+    components::{Animation, AnimationChannel, LayeredAnimation, ShipAnimationState},
+    resource::AnimationClips,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The content of an animation manifest, it declares all the layered animations
+#[derive(Debug, Deserialize)]
+pub struct AnimationManifest {
+    pub layered: BTreeMap<String, LayeredAnimationDefinition>,
+}
+
+/// One axis of a layered animation
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationChannelDefinition {
+    pub name: String,
+    /// How far apart two consecutive values of the channel are in the spritesheet
+    pub stride: u32,
+    /// The clip that the channel plays when the animation is created
+    pub initial_clip: String,
+}
+
+/// An animation whose sprite index is the sum of its channels' values, each multiplied by the
+/// channel's stride
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayeredAnimationDefinition {
+    pub channels: Vec<AnimationChannelDefinition>,
+    /// The clip that a channel plays in each state of the ship, indexed by the channel's name.
+    /// Channels that are left out keep their clip whatever the state is. Only the ship switches
+    /// clips on its own, other layered animations leave this out.
+    #[serde(default)]
+    pub state_clips: BTreeMap<String, BTreeMap<ShipAnimationState, String>>,
+}
+
+impl LayeredAnimationDefinition {
+    /// Create the component that plays this animation
+    pub fn instantiate(&self) -> LayeredAnimation {
+        LayeredAnimation {
+            channels: self
+                .channels
+                .iter()
+                .map(|channel| AnimationChannel {
+                    name: channel.name.clone(),
+                    stride: channel.stride,
+                    animation: Animation::new(&channel.initial_clip),
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the channels that have a clip for the state, with that clip
+    pub fn state_clips(&self, state: ShipAnimationState) -> impl Iterator<Item = (&str, &str)> {
+        self.state_clips.iter().filter_map(move |(channel, clips)| {
+            clips
+                .get(&state)
+                .map(|clip| (channel.as_str(), clip.as_str()))
+        })
+    }
+}
+
+/// Errors that make an animation manifest unusable
+#[derive(Debug)]
+pub enum AnimationError {
+    Io(PathBuf, io::Error),
+    Parse(ron::Error),
+    NoChannel {
+        animation: String,
+    },
+    DuplicateChannel {
+        animation: String,
+        channel: String,
+    },
+    InvalidStride {
+        animation: String,
+        channel: String,
+    },
+    UnknownChannel {
+        animation: String,
+        channel: String,
+    },
+    UnknownClip {
+        animation: String,
+        clip: String,
+    },
+    MixedSheets {
+        animation: String,
+        first_clip: String,
+        second_clip: String,
+    },
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationError::Io(path, err) => {
+                write!(f, "could not read {}: {}", path.display(), err)
+            }
+            AnimationError::Parse(err) => write!(f, "invalid manifest: {}", err),
+            AnimationError::NoChannel { animation } => {
+                write!(f, "animation \"{}\" has no channel", animation)
+            }
+            AnimationError::DuplicateChannel { animation, channel } => write!(
+                f,
+                "channel \"{}\" is declared twice in animation \"{}\"",
+                channel, animation
+            ),
+            AnimationError::InvalidStride { animation, channel } => write!(
+                f,
+                "channel \"{}\" of animation \"{}\" has a stride of zero",
+                channel, animation
+            ),
+            AnimationError::UnknownChannel { animation, channel } => write!(
+                f,
+                "animation \"{}\" has clips for channel \"{}\" but no such channel",
+                animation, channel
+            ),
+            AnimationError::UnknownClip { animation, clip } => write!(
+                f,
+                "animation \"{}\" plays clip \"{}\" which is not declared",
+                animation, clip
+            ),
+            AnimationError::MixedSheets {
+                animation,
+                first_clip,
+                second_clip,
+            } => write!(
+                f,
+                "animation \"{}\" plays clips \"{}\" and \"{}\" which are on different sheets",
+                animation, first_clip, second_clip
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+/// All the layered animations that can be played, indexed by their name
+#[derive(Debug)]
+pub struct LayeredAnimations(HashMap<String, LayeredAnimationDefinition>);
+
+impl LayeredAnimations {
+    /// Read and validate a manifest file, the clips it plays have to be among the given ones
+    pub fn load<P: AsRef<Path>>(path: P, clips: &AnimationClips) -> Result<Self, AnimationError> {
+        let path = path.as_ref();
+        let content =
+            fs::read_to_string(path).map_err(|err| AnimationError::Io(path.to_path_buf(), err))?;
+        Self::parse(&content, clips)
+    }
+
+    /// Parse and validate the content of a manifest, the clips it plays have to be among the given
+    /// ones
+    pub fn parse(content: &str, clips: &AnimationClips) -> Result<Self, AnimationError> {
+        let manifest: AnimationManifest =
+            ron::de::from_str(content).map_err(AnimationError::Parse)?;
+
+        for (animation, definition) in manifest.layered.iter() {
+            if definition.channels.is_empty() {
+                return Err(AnimationError::NoChannel {
+                    animation: animation.clone(),
+                });
+            }
+            for (channel_idx, channel) in definition.channels.iter().enumerate() {
+                if channel.stride == 0 {
+                    return Err(AnimationError::InvalidStride {
+                        animation: animation.clone(),
+                        channel: channel.name.clone(),
+                    });
+                }
+                if definition.channels[..channel_idx]
+                    .iter()
+                    .any(|other| other.name == channel.name)
+                {
+                    return Err(AnimationError::DuplicateChannel {
+                        animation: animation.clone(),
+                        channel: channel.name.clone(),
+                    });
+                }
+            }
+            for channel in definition.state_clips.keys() {
+                if !definition
+                    .channels
+                    .iter()
+                    .any(|declared| declared.name == *channel)
+                {
+                    return Err(AnimationError::UnknownChannel {
+                        animation: animation.clone(),
+                        channel: channel.clone(),
+                    });
+                }
+            }
+
+            // All the channels are drawn with a single sprite, so their clips share a sheet
+            let played_clips = definition
+                .channels
+                .iter()
+                .map(|channel| &channel.initial_clip)
+                .chain(
+                    definition
+                        .state_clips
+                        .values()
+                        .flat_map(|clips| clips.values()),
+                );
+            let mut first_clip: Option<(&String, &String)> = None;
+            for clip in played_clips {
+                let sheet = match clips.get(clip) {
+                    Some(definition) => &definition.sheet,
+                    None => {
+                        return Err(AnimationError::UnknownClip {
+                            animation: animation.clone(),
+                            clip: clip.clone(),
+                        })
+                    }
+                };
+                match first_clip {
+                    Some((first_clip, first_sheet)) if first_sheet != sheet => {
+                        return Err(AnimationError::MixedSheets {
+                            animation: animation.clone(),
+                            first_clip: first_clip.clone(),
+                            second_clip: clip.clone(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => first_clip = Some((clip, sheet)),
+                }
+            }
+        }
+
+        Ok(Self(manifest.layered.into_iter().collect()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LayeredAnimationDefinition> {
+        self.0.get(name)
+    }
+
+    pub fn insert(&mut self, name: &str, definition: LayeredAnimationDefinition) {
+        self.0.insert(name.to_string(), definition);
+    }
+}
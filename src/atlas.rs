@@ -0,0 +1,408 @@
+use crate::resource::{AnimationClip, AnimationClips, AnimationFrame, AnimationMode};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The content of an atlas manifest, it declares all the spritesheets, how they are cut and the
+/// animation clips that are played on them
+#[derive(Debug, Deserialize)]
+pub struct AtlasManifest {
+    pub sheets: BTreeMap<String, SheetDefinition>,
+    #[serde(default)]
+    pub clips: BTreeMap<String, ClipDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SheetDefinition {
+    /// Path to the texture, relative to the assets folder
+    pub texture: String,
+    pub layout: SheetLayout,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum SheetLayout {
+    /// Tiles of the same size, indexed from left to right then from top to bottom. The first
+    /// tiles can be given a name.
+    Grid {
+        tile_size: (f32, f32),
+        columns: usize,
+        rows: usize,
+        #[serde(default)]
+        names: Vec<String>,
+    },
+    /// Named rectangles inside of a texture of the given size, indexed in declaration order
+    Regions {
+        size: (f32, f32),
+        regions: Vec<RegionDefinition>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegionDefinition {
+    pub name: String,
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+/// An animation clip, all of its frames are taken from the same sheet
+#[derive(Debug, Deserialize)]
+pub struct ClipDefinition {
+    pub sheet: String,
+    pub frames: Vec<ClipFrame>,
+    /// How long each frame is shown, in seconds
+    pub frame_duration: f32,
+    pub mode: AnimationMode,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum ClipFrame {
+    /// A tile of the sheet by its index. In a channel of a layered animation, this is the
+    /// channel's value.
+    Tile(u32),
+    /// A named sprite, it has to be declared in the clip's sheet
+    Sprite(String),
+}
+
+/// Where a named sprite can be found
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteRef {
+    pub sheet: String,
+    pub index: u32,
+}
+
+/// Errors that make an atlas manifest unusable
+#[derive(Debug)]
+pub enum AtlasError {
+    Io(PathBuf, io::Error),
+    Parse(ron::Error),
+    EmptyTexture {
+        sheet: String,
+    },
+    EmptyGrid {
+        sheet: String,
+    },
+    InvalidTileSize {
+        sheet: String,
+    },
+    TooManyNames {
+        sheet: String,
+        names: usize,
+        tiles: usize,
+    },
+    InvalidRegion {
+        sheet: String,
+        region: String,
+    },
+    DuplicateSprite {
+        name: String,
+        first_sheet: String,
+        second_sheet: String,
+    },
+    EmptyClip {
+        clip: String,
+    },
+    InvalidFrameDuration {
+        clip: String,
+    },
+    UnknownSheet {
+        clip: String,
+        sheet: String,
+    },
+    TileOutOfRange {
+        clip: String,
+        sheet: String,
+        index: u32,
+        tiles: usize,
+    },
+    UnknownSprite {
+        clip: String,
+        sheet: String,
+        sprite: String,
+    },
+    MissingClip {
+        clip: String,
+    },
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+            AtlasError::Parse(err) => write!(f, "invalid manifest: {}", err),
+            AtlasError::EmptyTexture { sheet } => {
+                write!(f, "sheet \"{}\" has no texture path", sheet)
+            }
+            AtlasError::EmptyGrid { sheet } => {
+                write!(f, "sheet \"{}\" has a grid without columns or rows", sheet)
+            }
+            AtlasError::InvalidTileSize { sheet } => {
+                write!(f, "sheet \"{}\" has tiles without a positive size", sheet)
+            }
+            AtlasError::TooManyNames {
+                sheet,
+                names,
+                tiles,
+            } => write!(
+                f,
+                "sheet \"{}\" names {} tiles but its grid only has {}",
+                sheet, names, tiles
+            ),
+            AtlasError::InvalidRegion { sheet, region } => write!(
+                f,
+                "region \"{}\" of sheet \"{}\" is empty or lies outside of the texture",
+                region, sheet
+            ),
+            AtlasError::DuplicateSprite {
+                name,
+                first_sheet,
+                second_sheet,
+            } => write!(
+                f,
+                "sprite \"{}\" is declared in sheet \"{}\" and again in sheet \"{}\"",
+                name, first_sheet, second_sheet
+            ),
+            AtlasError::EmptyClip { clip } => write!(f, "clip \"{}\" has no frame", clip),
+            AtlasError::InvalidFrameDuration { clip } => {
+                write!(
+                    f,
+                    "clip \"{}\" has frames without a positive duration",
+                    clip
+                )
+            }
+            AtlasError::UnknownSheet { clip, sheet } => {
+                write!(
+                    f,
+                    "clip \"{}\" uses sheet \"{}\" which is not declared",
+                    clip, sheet
+                )
+            }
+            AtlasError::TileOutOfRange {
+                clip,
+                sheet,
+                index,
+                tiles,
+            } => write!(
+                f,
+                "clip \"{}\" shows tile {} but sheet \"{}\" only has {}",
+                clip, index, sheet, tiles
+            ),
+            AtlasError::UnknownSprite {
+                clip,
+                sheet,
+                sprite,
+            } => write!(
+                f,
+                "clip \"{}\" shows sprite \"{}\" which is not declared in sheet \"{}\"",
+                clip, sprite, sheet
+            ),
+            AtlasError::MissingClip { clip } => {
+                write!(
+                    f,
+                    "clip \"{}\" is played by the game but not declared",
+                    clip
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+/// A validated atlas manifest, with an index of all the named sprites and its clips ready to be
+/// played
+#[derive(Debug)]
+pub struct SpriteAtlases {
+    manifest: AtlasManifest,
+    sprites: HashMap<String, SpriteRef>,
+    clips: AnimationClips,
+}
+
+impl SpriteAtlases {
+    /// Read and validate a manifest file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AtlasError> {
+        let path = path.as_ref();
+        let content =
+            fs::read_to_string(path).map_err(|err| AtlasError::Io(path.to_path_buf(), err))?;
+        Self::parse(&content)
+    }
+
+    /// Parse and validate the content of a manifest
+    pub fn parse(content: &str) -> Result<Self, AtlasError> {
+        let manifest: AtlasManifest = ron::de::from_str(content).map_err(AtlasError::Parse)?;
+
+        let mut sprites: HashMap<String, SpriteRef> = HashMap::new();
+        let mut tiles: HashMap<&String, usize> = HashMap::new();
+        for (sheet, definition) in manifest.sheets.iter() {
+            if definition.texture.is_empty() {
+                return Err(AtlasError::EmptyTexture {
+                    sheet: sheet.clone(),
+                });
+            }
+
+            let names: Vec<&String> = match &definition.layout {
+                SheetLayout::Grid {
+                    tile_size,
+                    columns,
+                    rows,
+                    names,
+                } => {
+                    if *columns == 0 || *rows == 0 {
+                        return Err(AtlasError::EmptyGrid {
+                            sheet: sheet.clone(),
+                        });
+                    }
+                    if tile_size.0 <= 0. || tile_size.1 <= 0. {
+                        return Err(AtlasError::InvalidTileSize {
+                            sheet: sheet.clone(),
+                        });
+                    }
+                    if names.len() > columns * rows {
+                        return Err(AtlasError::TooManyNames {
+                            sheet: sheet.clone(),
+                            names: names.len(),
+                            tiles: columns * rows,
+                        });
+                    }
+                    tiles.insert(sheet, columns * rows);
+                    names.iter().collect()
+                }
+                SheetLayout::Regions { size, regions } => {
+                    for region in regions.iter() {
+                        if region.min.0 < 0.
+                            || region.min.1 < 0.
+                            || region.max.0 > size.0
+                            || region.max.1 > size.1
+                            || region.min.0 >= region.max.0
+                            || region.min.1 >= region.max.1
+                        {
+                            return Err(AtlasError::InvalidRegion {
+                                sheet: sheet.clone(),
+                                region: region.name.clone(),
+                            });
+                        }
+                    }
+                    tiles.insert(sheet, regions.len());
+                    regions.iter().map(|region| &region.name).collect()
+                }
+            };
+
+            for (index, name) in names.into_iter().enumerate() {
+                if let Some(sprite) = sprites.get(name) {
+                    return Err(AtlasError::DuplicateSprite {
+                        name: name.clone(),
+                        first_sheet: sprite.sheet.clone(),
+                        second_sheet: sheet.clone(),
+                    });
+                }
+                sprites.insert(
+                    name.clone(),
+                    SpriteRef {
+                        sheet: sheet.clone(),
+                        index: index as u32,
+                    },
+                );
+            }
+        }
+
+        let mut clips = AnimationClips::default();
+        for (clip, definition) in manifest.clips.iter() {
+            if definition.frames.is_empty() {
+                return Err(AtlasError::EmptyClip { clip: clip.clone() });
+            }
+            if !definition.frame_duration.is_finite() || definition.frame_duration <= 0. {
+                return Err(AtlasError::InvalidFrameDuration { clip: clip.clone() });
+            }
+            let sheet_tiles = match tiles.get(&definition.sheet) {
+                Some(sheet_tiles) => *sheet_tiles,
+                None => {
+                    return Err(AtlasError::UnknownSheet {
+                        clip: clip.clone(),
+                        sheet: definition.sheet.clone(),
+                    })
+                }
+            };
+
+            let mut frames = Vec::with_capacity(definition.frames.len());
+            for frame in definition.frames.iter() {
+                let index = match frame {
+                    ClipFrame::Tile(index) => {
+                        if *index as usize >= sheet_tiles {
+                            return Err(AtlasError::TileOutOfRange {
+                                clip: clip.clone(),
+                                sheet: definition.sheet.clone(),
+                                index: *index,
+                                tiles: sheet_tiles,
+                            });
+                        }
+                        *index
+                    }
+                    ClipFrame::Sprite(name) => match sprites.get(name) {
+                        Some(sprite) if sprite.sheet == definition.sheet => sprite.index,
+                        _ => {
+                            return Err(AtlasError::UnknownSprite {
+                                clip: clip.clone(),
+                                sheet: definition.sheet.clone(),
+                                sprite: name.clone(),
+                            })
+                        }
+                    },
+                };
+                frames.push(AnimationFrame {
+                    index,
+                    duration: Duration::from_secs_f32(definition.frame_duration),
+                });
+            }
+            clips.insert(
+                clip,
+                AnimationClip::new(&definition.sheet, frames, definition.mode),
+            );
+        }
+
+        Ok(Self {
+            manifest,
+            sprites,
+            clips,
+        })
+    }
+
+    /// Look up a sprite by its name
+    pub fn sprite(&self, name: &str) -> Option<&SpriteRef> {
+        self.sprites.get(name)
+    }
+
+    pub fn sheets(&self) -> impl Iterator<Item = (&String, &SheetDefinition)> {
+        self.manifest.sheets.iter()
+    }
+
+    pub fn clips(&self) -> &AnimationClips {
+        &self.clips
+    }
+
+    /// Make sure that the clips are declared, so the entities that play them can be shown
+    pub fn require_clips(&self, clips: &[&str]) -> Result<(), AtlasError> {
+        match clips.iter().find(|clip| self.clips.get(clip).is_none()) {
+            Some(clip) => Err(AtlasError::MissingClip {
+                clip: clip.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Get the path to a file in the assets folder, the folder is looked up the same way as Bevy's
+/// asset server does
+pub fn asset_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let root = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        PathBuf::from(manifest_dir)
+    } else {
+        env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_default()
+    };
+    root.join("assets").join(path)
+}
@@ -0,0 +1,230 @@
+use crate::constant::{ARENA_SCALE, MAX_PLAYERS};
+use std::{fmt, path::PathBuf, str::FromStr};
+
+/// Description of the flags that is shown by `--help` and when the arguments can not be used
+pub const USAGE: &str = "\
+Usage: space-shooter [OPTIONS]
+
+Options:
+    --scale <SCALE>     Size of an arena unit in pixels when the window opens
+    --players <N>       Number of players in the run from the start, up to 2
+    --seed <SEED>       Seed of the random number generator
+    --config <PATH>     Path to the game config file
+    --record <PATH>     Record the player's input to a replay file
+    --replay <PATH>     Play a replay file back instead of reading the player's input, with the
+                        seed, the players and the god mode it was recorded with
+    --pointer           The ship follows the mouse cursor, the mouse buttons fire and bomb
+    --host <PORT>       Wait for a second player to join over the network on this port
+    --connect <ADDR>    Join the game that is hosted at this address, e.g. 127.0.0.1:7000
+    --latency <MS>      Hold every packet back for this long when playing over the network
+    --packet-loss <%>   Drop this percentage of packets when playing over the network
+    --god               The ship can not be destroyed
+    --headless          Run the simulation without a window
+    -h, --help          Print this message";
+
+/// Options given on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliOptions {
+    pub scale: f32,
+    pub players: usize,
+    pub seed: Option<u64>,
+    pub config: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub pointer_control: bool,
+    pub host: Option<u16>,
+    pub connect: Option<String>,
+    /// Simulated latency in milliseconds
+    pub latency: u64,
+    /// Simulated packet loss in percent
+    pub packet_loss: f32,
+    pub god_mode: bool,
+    pub headless: bool,
+    pub help: bool,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        Self {
+            scale: ARENA_SCALE,
+            players: 1,
+            seed: None,
+            config: None,
+            record: None,
+            replay: None,
+            pointer_control: false,
+            host: None,
+            connect: None,
+            latency: 0,
+            packet_loss: 0.,
+            god_mode: false,
+            headless: false,
+            help: false,
+        }
+    }
+}
+
+/// Errors that make the command line arguments unusable
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownFlag(String),
+    MissingValue(&'static str),
+    UnexpectedValue(&'static str),
+    InvalidValue { flag: &'static str, value: String },
+    Conflict(&'static str, &'static str),
+    NotNetworked(&'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownFlag(flag) => write!(f, "unknown flag `{}`", flag),
+            CliError::MissingValue(flag) => write!(f, "`{}` needs a value", flag),
+            CliError::UnexpectedValue(flag) => write!(f, "`{}` does not take a value", flag),
+            CliError::InvalidValue { flag, value } => {
+                write!(f, "`{}` is not a valid value for `{}`", value, flag)
+            }
+            CliError::Conflict(first, second) => {
+                write!(f, "`{}` can not be used together with `{}`", first, second)
+            }
+            CliError::NotNetworked(flag) => write!(
+                f,
+                "`{}` can only be used together with `--host` or `--connect`",
+                flag
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl CliOptions {
+    /// Parse the arguments that come after the program's name. Values can be given either as
+    /// `--flag value` or as `--flag=value`.
+    pub fn parse<I, S>(args: I) -> Result<Self, CliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut options = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.find('=') {
+                Some(idx) if arg.starts_with("--") => {
+                    (arg[..idx].to_string(), Some(arg[idx + 1..].to_string()))
+                }
+                _ => (arg, None),
+            };
+
+            match flag.as_str() {
+                "--scale" => {
+                    let scale: f32 = parse_value("--scale", inline_value, &mut args)?;
+                    if !(scale > 0. && scale.is_finite()) {
+                        return Err(CliError::InvalidValue {
+                            flag: "--scale",
+                            value: scale.to_string(),
+                        });
+                    }
+                    options.scale = scale;
+                }
+                "--players" => {
+                    let players: usize = parse_value("--players", inline_value, &mut args)?;
+                    if players == 0 || players > MAX_PLAYERS {
+                        return Err(CliError::InvalidValue {
+                            flag: "--players",
+                            value: players.to_string(),
+                        });
+                    }
+                    options.players = players;
+                }
+                "--seed" => options.seed = Some(parse_value("--seed", inline_value, &mut args)?),
+                "--config" => {
+                    options.config = Some(parse_value("--config", inline_value, &mut args)?)
+                }
+                "--record" => {
+                    options.record = Some(parse_value("--record", inline_value, &mut args)?)
+                }
+                "--replay" => {
+                    options.replay = Some(parse_value("--replay", inline_value, &mut args)?)
+                }
+                "--pointer" => options.pointer_control = parse_switch("--pointer", inline_value)?,
+                "--host" => options.host = Some(parse_value("--host", inline_value, &mut args)?),
+                "--connect" => {
+                    options.connect = Some(parse_value("--connect", inline_value, &mut args)?)
+                }
+                "--latency" => options.latency = parse_value("--latency", inline_value, &mut args)?,
+                "--packet-loss" => {
+                    let packet_loss: f32 = parse_value("--packet-loss", inline_value, &mut args)?;
+                    if !(0. ..=100.).contains(&packet_loss) {
+                        return Err(CliError::InvalidValue {
+                            flag: "--packet-loss",
+                            value: packet_loss.to_string(),
+                        });
+                    }
+                    options.packet_loss = packet_loss;
+                }
+                "--god" => options.god_mode = parse_switch("--god", inline_value)?,
+                "--headless" => options.headless = parse_switch("--headless", inline_value)?,
+                "-h" | "--help" => options.help = parse_switch("--help", inline_value)?,
+                _ => return Err(CliError::UnknownFlag(flag)),
+            }
+        }
+
+        if options.record.is_some() && options.replay.is_some() {
+            return Err(CliError::Conflict("--record", "--replay"));
+        }
+        if options.seed.is_some() && options.replay.is_some() {
+            return Err(CliError::Conflict("--seed", "--replay"));
+        }
+
+        // The inputs of a game played over the network are not recorded, and the host decides
+        // everything that both games have to agree on
+        let network_flag = match (options.host, &options.connect) {
+            (Some(_), Some(_)) => return Err(CliError::Conflict("--host", "--connect")),
+            (Some(_), None) => Some("--host"),
+            (None, Some(_)) => Some("--connect"),
+            (None, None) => None,
+        };
+        if let Some(network_flag) = network_flag {
+            if options.record.is_some() {
+                return Err(CliError::Conflict("--record", network_flag));
+            }
+            if options.replay.is_some() {
+                return Err(CliError::Conflict("--replay", network_flag));
+            }
+        } else if options.latency > 0 {
+            return Err(CliError::NotNetworked("--latency"));
+        } else if options.packet_loss > 0. {
+            return Err(CliError::NotNetworked("--packet-loss"));
+        }
+        if options.connect.is_some() && options.seed.is_some() {
+            return Err(CliError::Conflict("--seed", "--connect"));
+        }
+        Ok(options)
+    }
+}
+
+/// Get the value of a flag, either from after its `=` or from the next argument
+fn parse_value<T, I>(
+    flag: &'static str,
+    inline_value: Option<String>,
+    args: &mut I,
+) -> Result<T, CliError>
+where
+    T: FromStr,
+    I: Iterator<Item = String>,
+{
+    let value = inline_value
+        .or_else(|| args.next())
+        .ok_or(CliError::MissingValue(flag))?;
+    value
+        .parse()
+        .map_err(|_| CliError::InvalidValue { flag, value })
+}
+
+fn parse_switch(flag: &'static str, inline_value: Option<String>) -> Result<bool, CliError> {
+    match inline_value {
+        Some(_) => Err(CliError::UnexpectedValue(flag)),
+        None => Ok(true),
+    }
+}
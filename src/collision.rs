@@ -0,0 +1,353 @@
+use bevy::math::Vec2;
+
+/// A uniform grid over an area, every item is put in the cells that its box overlaps. Finding the
+/// items near a box only looks at the cells it overlaps instead of at every item.
+///
+/// Boxes that stick out of the area are put in the cells at its border, so nothing is missed when
+/// entities leave the arena.
+pub struct SpatialGrid<T> {
+    min: Vec2,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    items: Vec<T>,
+    /// Indices of the items that a query has found, kept so queries do not allocate
+    found_indices: Vec<usize>,
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    /// Create an empty grid that covers the area with the given center and size
+    pub fn new(center: Vec2, size: Vec2, cell_size: f32) -> Self {
+        let columns = ((size.x() / cell_size).ceil() as usize).max(1);
+        let rows = ((size.y() / cell_size).ceil() as usize).max(1);
+        Self {
+            min: center - size / 2.,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            items: Vec::new(),
+            found_indices: Vec::new(),
+        }
+    }
+
+    /// Remove every item, the memory is kept for the next items
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+        self.items.clear();
+    }
+
+    /// Add an item whose box has the given center and size
+    pub fn insert(&mut self, item: T, center: Vec2, size: Vec2) {
+        let item_idx = self.items.len();
+        self.items.push(item);
+        let (columns, rows) = self.cell_range(center, size);
+        for row in rows {
+            for column in columns.clone() {
+                self.cells[row * self.columns + column].push(item_idx);
+            }
+        }
+    }
+
+    /// Get the items that share a cell with the given box, in the order they were added. The items
+    /// may not overlap the box, that is for the caller to check.
+    pub fn query(&mut self, center: Vec2, size: Vec2, found: &mut Vec<T>) {
+        found.clear();
+        self.found_indices.clear();
+        let (columns, rows) = self.cell_range(center, size);
+        for row in rows {
+            for column in columns.clone() {
+                self.found_indices
+                    .extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+        // Items that span several cells are found more than once
+        self.found_indices.sort_unstable();
+        self.found_indices.dedup();
+        let items = &self.items;
+        found.extend(self.found_indices.iter().map(|item_idx| items[*item_idx]));
+    }
+
+    fn cell_range(
+        &self,
+        center: Vec2,
+        size: Vec2,
+    ) -> (
+        std::ops::RangeInclusive<usize>,
+        std::ops::RangeInclusive<usize>,
+    ) {
+        let min = (center - size / 2. - self.min) / self.cell_size;
+        let max = (center + size / 2. - self.min) / self.cell_size;
+        let cell = |value: f32, count: usize| (value.floor().max(0.) as usize).min(count - 1);
+        (
+            cell(min.x(), self.columns)..=cell(max.x(), self.columns),
+            cell(min.y(), self.rows)..=cell(max.y(), self.rows),
+        )
+    }
+}
+
+/// A shape in the arena, the position of its entity is already applied. Shapes that only touch do
+/// not intersect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Aabb {
+        center: Vec2,
+        half_extents: Vec2,
+    },
+    /// The points that are closer than the radius to the segment between the two ends
+    Capsule {
+        start: Vec2,
+        end: Vec2,
+        radius: f32,
+    },
+}
+
+impl Shape {
+    pub fn intersects(&self, other: &Shape) -> bool {
+        match (*self, *other) {
+            (
+                Shape::Aabb {
+                    center,
+                    half_extents,
+                },
+                Shape::Aabb {
+                    center: other_center,
+                    half_extents: other_half_extents,
+                },
+            ) => {
+                let distance = (center - other_center).abs();
+                let reach = half_extents + other_half_extents;
+                distance.x() < reach.x() && distance.y() < reach.y()
+            }
+            // Every other pair is a distance between a point or a segment and the other shape
+            _ => {
+                let (shape, radius) = self.core();
+                let (other_shape, other_radius) = other.core();
+                let reach = radius + other_radius;
+                let distance_squared = core_distance_squared(&shape, &other_shape);
+                // Shapes without rounding still intersect when one is inside of the other
+                distance_squared < reach * reach || (reach == 0. && distance_squared == 0.)
+            }
+        }
+    }
+
+    /// The shape that contains every place the shape went through while it moved by the given
+    /// motion to where it is now. Circles and capsules that move along their segment are swept
+    /// exactly, the other shapes are swept into a shape that is a bit larger so a hit is never
+    /// missed.
+    pub fn swept(&self, motion: Vec2) -> Shape {
+        match *self {
+            Shape::Circle { center, radius } => Shape::Capsule {
+                start: center - motion,
+                end: center,
+                radius,
+            },
+            Shape::Aabb {
+                center,
+                half_extents,
+            } => Shape::Aabb {
+                center: center - motion / 2.,
+                half_extents: half_extents + motion.abs() / 2.,
+            },
+            Shape::Capsule { start, end, radius } => {
+                let segment = end - start;
+                let cross = segment.x() * motion.y() - segment.y() * motion.x();
+                if cross.abs() <= f32::EPSILON * segment.length() * motion.length() {
+                    // The segment and its past positions are on the same line, the capsule gets
+                    // longer to cover the farthest two of them
+                    let ends = [start, end, start - motion, end - motion];
+                    let direction = if segment.length() > 0. {
+                        segment
+                    } else {
+                        motion
+                    };
+                    let along = |point: &Vec2| point.dot(direction);
+                    let first = ends.iter().copied().fold(start, |min, point| {
+                        if along(&point) < along(&min) {
+                            point
+                        } else {
+                            min
+                        }
+                    });
+                    let last = ends.iter().copied().fold(end, |max, point| {
+                        if along(&point) > along(&max) {
+                            point
+                        } else {
+                            max
+                        }
+                    });
+                    Shape::Capsule {
+                        start: first,
+                        end: last,
+                        radius,
+                    }
+                } else {
+                    // The middle of the segment is swept and the rounding grows to the segment
+                    let middle = (start + end) / 2.;
+                    Shape::Capsule {
+                        start: middle - motion,
+                        end: middle,
+                        radius: radius + segment.length() / 2.,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Smallest box that contains the shape, as its center and its size
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match *self {
+            Shape::Circle { center, radius } => (center, Vec2::splat(radius * 2.)),
+            Shape::Aabb {
+                center,
+                half_extents,
+            } => (center, half_extents * 2.),
+            Shape::Capsule { start, end, radius } => (
+                (start + end) / 2.,
+                (end - start).abs() + Vec2::splat(radius * 2.),
+            ),
+        }
+    }
+
+    /// The shape without its rounding, and the radius of the rounding
+    fn core(&self) -> (Shape, f32) {
+        match *self {
+            Shape::Circle { center, radius } => (
+                Shape::Capsule {
+                    start: center,
+                    end: center,
+                    radius: 0.,
+                },
+                radius,
+            ),
+            Shape::Aabb { .. } => (*self, 0.),
+            Shape::Capsule { start, end, radius } => (
+                Shape::Capsule {
+                    start,
+                    end,
+                    radius: 0.,
+                },
+                radius,
+            ),
+        }
+    }
+}
+
+/// Squared distance between two shapes without rounding, a segment and a segment or a box
+fn core_distance_squared(shape: &Shape, other: &Shape) -> f32 {
+    match (*shape, *other) {
+        (
+            Shape::Capsule { start, end, .. },
+            Shape::Capsule {
+                start: other_start,
+                end: other_end,
+                ..
+            },
+        ) => segment_segment_distance_squared(start, end, other_start, other_end),
+        (
+            Shape::Capsule { start, end, .. },
+            Shape::Aabb {
+                center,
+                half_extents,
+            },
+        )
+        | (
+            Shape::Aabb {
+                center,
+                half_extents,
+            },
+            Shape::Capsule { start, end, .. },
+        ) => segment_aabb_distance_squared(start, end, center, half_extents),
+        _ => unreachable!("Two boxes are tested without their distance"),
+    }
+}
+
+fn point_aabb_distance_squared(point: Vec2, center: Vec2, half_extents: Vec2) -> f32 {
+    let outside = ((point - center).abs() - half_extents).max(Vec2::zero());
+    outside.dot(outside)
+}
+
+fn segment_aabb_distance_squared(start: Vec2, end: Vec2, center: Vec2, half_extents: Vec2) -> f32 {
+    // When the segment is not inside the box and does not cross it, the closest points are an end
+    // of the segment and the box or a side of the box and the segment
+    let corners = [
+        center + half_extents * Vec2::new(-1., -1.),
+        center + half_extents * Vec2::new(1., -1.),
+        center + half_extents * Vec2::new(1., 1.),
+        center + half_extents * Vec2::new(-1., 1.),
+    ];
+    let mut distance_squared = point_aabb_distance_squared(start, center, half_extents)
+        .min(point_aabb_distance_squared(end, center, half_extents));
+    for (corner_idx, corner) in corners.iter().enumerate() {
+        let next_corner = corners[(corner_idx + 1) % corners.len()];
+        distance_squared = distance_squared.min(segment_segment_distance_squared(
+            start,
+            end,
+            *corner,
+            next_corner,
+        ));
+    }
+    distance_squared
+}
+
+/// Squared distance between the closest points of two segments, either of them can be a point
+fn segment_segment_distance_squared(
+    start: Vec2,
+    end: Vec2,
+    other_start: Vec2,
+    other_end: Vec2,
+) -> f32 {
+    let direction = end - start;
+    let other_direction = other_end - other_start;
+    if segments_cross(start, end, other_start, other_end) {
+        return 0.;
+    }
+    // Segments that do not cross are closest at one of their ends
+    point_segment_distance_squared(start, other_start, other_direction)
+        .min(point_segment_distance_squared(
+            end,
+            other_start,
+            other_direction,
+        ))
+        .min(point_segment_distance_squared(
+            other_start,
+            start,
+            direction,
+        ))
+        .min(point_segment_distance_squared(other_end, start, direction))
+}
+
+fn point_segment_distance_squared(point: Vec2, start: Vec2, direction: Vec2) -> f32 {
+    let length_squared = direction.dot(direction);
+    let t = if length_squared > 0. {
+        ((point - start).dot(direction) / length_squared)
+            .max(0.)
+            .min(1.)
+    } else {
+        0.
+    };
+    let offset = point - (start + direction * t);
+    offset.dot(offset)
+}
+
+/// Check if two segments have a point in common where they cross each other. Segments that only
+/// touch or lie on the same line are left to the distance between their ends.
+fn segments_cross(start: Vec2, end: Vec2, other_start: Vec2, other_end: Vec2) -> bool {
+    let side = |a: Vec2, b: Vec2, point: Vec2| {
+        let ab = b - a;
+        let ap = point - a;
+        ab.x() * ap.y() - ab.y() * ap.x()
+    };
+    let d1 = side(other_start, other_end, start);
+    let d2 = side(other_start, other_end, end);
+    let d3 = side(start, end, other_start);
+    let d4 = side(start, end, other_end);
+    ((d1 > 0. && d2 < 0.) || (d1 < 0. && d2 > 0.)) && ((d3 > 0. && d4 < 0.) || (d3 < 0. && d4 > 0.))
+}
@@ -1,8 +1,14 @@
+use crate::collision::Shape;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// Component that says which player controls a ship, it is the player's index in the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player(pub usize);
+
 /// Different states of the  when moving left/right
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Deserialize)]
 pub enum ShipAnimationState {
     FullLeft,
     HalfLeft,
@@ -12,7 +18,7 @@ pub enum ShipAnimationState {
 }
 
 /// Component that marks an entity to be a ship in the game
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ship {
     pub animation_state: ShipAnimationState,
     pub move_speed: f32,
@@ -27,54 +33,375 @@ pub enum EnemyVariant {
     Big,
 }
 
+/// What blew up, determines how strong the feedback of an explosion is
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExplosionKind {
+    Enemy(EnemyVariant),
+    Ship,
+}
+
 /// Component marks an entity to be an enemy
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Enemy {
     pub variant: EnemyVariant,
 }
 
 /// Component determines the spawn rate and spawn probability of entities
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EnemySpawner {
     pub timer: Timer,
     pub weights: Vec<(EnemyVariant, u8)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Laser {
     pub source: Entity,
+    /// The player whose ship fired the laser, who is credited for what it destroys
+    pub player: Option<Player>,
 }
 
-#[derive(Debug)]
+/// Component that says which layers an entity is on and which layers it collides with. Two
+/// entities only collide when each of them is on a layer that the other one collides with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionLayer {
+    pub memberships: u8,
+    pub mask: u8,
+}
+
+impl CollisionLayer {
+    pub const PLAYER: u8 = 1;
+    pub const PLAYER_PROJECTILE: u8 = 1 << 1;
+    pub const ENEMY: u8 = 1 << 2;
+    pub const ENEMY_PROJECTILE: u8 = 1 << 3;
+    pub const PICKUP: u8 = 1 << 4;
+    pub const HAZARD: u8 = 1 << 5;
+    /// Number of layers, every layer is one bit
+    pub const COUNT: usize = 6;
+
+    pub fn new(memberships: u8, mask: u8) -> Self {
+        Self { memberships, mask }
+    }
+
+    pub fn ship() -> Self {
+        Self::new(
+            Self::PLAYER,
+            Self::ENEMY_PROJECTILE | Self::PICKUP | Self::HAZARD,
+        )
+    }
+
+    pub fn ship_laser() -> Self {
+        Self::new(Self::PLAYER_PROJECTILE, Self::ENEMY | Self::HAZARD)
+    }
+
+    pub fn enemy() -> Self {
+        Self::new(Self::ENEMY, Self::PLAYER_PROJECTILE)
+    }
+
+    pub fn enemy_laser() -> Self {
+        Self::new(Self::ENEMY_PROJECTILE, Self::PLAYER)
+    }
+
+    pub fn is_on(self, layer: u8) -> bool {
+        self.memberships & layer != 0
+    }
+
+    pub fn collides_with(self, other: CollisionLayer) -> bool {
+        self.mask & other.memberships != 0 && other.mask & self.memberships != 0
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Weapon {
     pub cooldown_timer: Timer,
     pub laser_velocity: Velocity,
     pub laser_hit_box: HitBox,
     pub laser_time_to_live_duration: Duration,
-    pub laser_initial_sprite_idx: u32,
+    pub laser_animation_clip: String,
+    pub laser_collision_layer: CollisionLayer,
+    pub laser_bounds: ArenaBounds,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Explosion;
 
-/// Component that determines when to change the sprite and which sprite index to change to when
-/// doing simple animation
-#[derive(Debug)]
+/// Component that marks an entity which goes back to a pool instead of being despawned. It keeps
+/// its sprite while it waits in the pool, and the sprite is hidden until the entity is reused.
+#[derive(Debug, Clone, Copy)]
+pub struct Pooled;
+
+/// Component that plays a named animation clip, it keeps track of the clip's current frame and
+/// how long that frame has been shown
+#[derive(Debug, Clone)]
 pub struct Animation {
-    pub idx_delta: u32,
-    pub sprite_count: u32,
-    pub timer: Timer,
+    pub clip: String,
+    pub frame: usize,
+    pub frame_elapsed: f32,
+    pub reversed: bool,
+    pub finished: bool,
+}
+
+impl Animation {
+    pub fn new(clip: &str) -> Self {
+        Self {
+            clip: clip.to_string(),
+            frame: 0,
+            frame_elapsed: 0.,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    /// Start playing another clip from its first frame, nothing changes if the clip is already
+    /// being played
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            *self = Self::new(clip);
+        }
+    }
 }
 
+/// Component marks an entity to be one tile of a vertically scrolling background layer. All tiles
+/// of a layer are stacked on top of each other and move down at the same speed, a tile that goes
+/// past the bottom of the arena is moved back to the top of the stack.
+#[derive(Debug, Clone)]
+pub struct BackgroundLayer {
+    pub scroll_speed: f32,
+    pub tile_height: f32,
+    pub tile_count: u32,
+}
+
+/// Component that marks one of the black bars around the arena, they hide whatever is out of the
+/// arena when the window does not have the arena's shape
+#[derive(Debug)]
+pub struct LetterboxBar;
+
+/// Component for one line of text of the rebinding screen, the number is the line's index from
+/// the top
+#[derive(Debug)]
+pub struct RebindingLine(pub usize);
+
+/// Component that shakes a camera based on its amount of trauma. Trauma is added by impacts and
+/// decays over time, the camera's offset and roll grow with the square of the trauma.
 #[derive(Debug)]
-pub struct ConstrainedToArena;
+pub struct CameraShake {
+    pub trauma: f32,
+    pub trauma_decay: f32,
+    pub max_offset: f32,
+    pub max_roll: f32,
+    pub frequency: f32,
+}
+
+/// A named axis of a layered animation that plays its own clip
+#[derive(Debug, Clone)]
+pub struct AnimationChannel {
+    pub name: String,
+    pub stride: u32,
+    pub animation: Animation,
+}
+
+/// Component that plays one clip per channel at the same time, the frames of all the channels are
+/// combined into a single sprite index
+#[derive(Debug, Clone)]
+pub struct LayeredAnimation {
+    pub channels: Vec<AnimationChannel>,
+}
+
+impl LayeredAnimation {
+    /// Play a clip on the channel with the given name, see `Animation::play`
+    pub fn play(&mut self, channel: &str, clip: &str) {
+        if let Some(channel) = self
+            .channels
+            .iter_mut()
+            .find(|animation_channel| animation_channel.name == channel)
+        {
+            channel.animation.play(clip);
+        }
+    }
+}
+
+/// Component that says which sprite of a spritesheet shows the entity. The simulation only changes
+/// the index, the presentation is the one that draws the sprite.
+#[derive(Debug, Clone)]
+pub struct SheetSprite {
+    /// Sheet of the clip that the entity plays, the entity is not drawn until it has one
+    pub sheet: Option<String>,
+    pub index: u32,
+    /// Tint that the sprite is drawn with
+    pub color: Color,
+}
+
+impl Default for SheetSprite {
+    fn default() -> Self {
+        Self {
+            sheet: None,
+            index: 0,
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// What happens to an entity that reaches the edge of the arena
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoundsPolicy {
+    /// Stop at the edge
+    Clamp,
+    /// Leave the game once it is out of the arena
+    Despawn,
+    /// Come back in from the opposite edge once it is out of the arena
+    Wrap,
+    /// Bounce off the edge, the velocity is turned back on the axis of the edge
+    Bounce,
+}
+
+impl Default for BoundsPolicy {
+    fn default() -> Self {
+        BoundsPolicy::Despawn
+    }
+}
+
+/// Component that says what happens to an entity at the edge of the arena. The margin moves the
+/// edges out of the arena on every side, so an entity can go a bit further before it is stopped
+/// or removed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArenaBounds {
+    pub policy: BoundsPolicy,
+    pub margin: f32,
+}
+
+impl ArenaBounds {
+    pub fn new(policy: BoundsPolicy, margin: f32) -> Self {
+        Self { policy, margin }
+    }
+
+    /// How far from the center of the arena the center of an entity with the given size can go
+    /// before the entity touches the edge, and before it is out of the arena
+    pub fn limits(&self, arena_size: Vec2, size: Vec2) -> (Vec2, Vec2) {
+        let reach = arena_size / 2. + Vec2::splat(self.margin);
+        ((reach - size / 2.).max(Vec2::zero()), reach + size / 2.)
+    }
+
+    /// Check if an entity with the given size is out of the arena
+    pub fn is_out(&self, arena_size: Vec2, size: Vec2, position: Vec2) -> bool {
+        let (_, min_outside) = self.limits(arena_size, size);
+        let distance = position.abs();
+        distance.x() >= min_outside.x() || distance.y() >= min_outside.y()
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Velocity(pub Vec2);
 
+/// Component that marks an entity that is fast enough to go through a thin entity in one tick. It
+/// collides with everything it went through during the tick, not only with what is where it ended.
+#[derive(Debug, Clone, Copy)]
+pub struct ContinuousCollision;
+
+/// Component that keeps the translation an entity had before the last simulation tick, the entity
+/// is rendered in between its previous and its current translation
+#[derive(Debug, Clone, Copy)]
+pub struct PreviousTranslation(pub Vec3);
+
 /// Component that determines the smallest possible box that includes rendered the entity.
 #[derive(Debug, Clone, Copy)]
 pub struct HitBox(pub Vec2);
 
+/// Component that gives an entity the shape it collides with. Every shape is placed at an offset
+/// from the entity's translation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collider {
+    Circle {
+        offset: Vec2,
+        radius: f32,
+    },
+    Aabb {
+        offset: Vec2,
+        half_extents: Vec2,
+    },
+    /// The segment from `offset - half_segment` to `offset + half_segment`, rounded by the radius
+    Capsule {
+        offset: Vec2,
+        half_segment: Vec2,
+        radius: f32,
+    },
+    /// Several colliders, their offsets are from the offset of the compound
+    Compound {
+        offset: Vec2,
+        parts: Vec<Collider>,
+    },
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Collider::Circle {
+            offset: Vec2::zero(),
+            radius,
+        }
+    }
+
+    pub fn aabb(size: Vec2) -> Self {
+        Collider::Aabb {
+            offset: Vec2::zero(),
+            half_extents: size / 2.,
+        }
+    }
+
+    /// The capsule that fills a box of the given size, it lies along the longest side of the box
+    pub fn capsule_in(size: Vec2) -> Self {
+        let radius = size.x().min(size.y()) / 2.;
+        let half_segment = if size.x() > size.y() {
+            Vec2::new(size.x() / 2. - radius, 0.)
+        } else {
+            Vec2::new(0., size.y() / 2. - radius)
+        };
+        Collider::Capsule {
+            offset: Vec2::zero(),
+            half_segment,
+            radius,
+        }
+    }
+
+    /// Add the shapes of the collider of an entity at the given position
+    pub fn shapes(&self, position: Vec2, shapes: &mut Vec<Shape>) {
+        match self {
+            Collider::Circle { offset, radius } => shapes.push(Shape::Circle {
+                center: position + *offset,
+                radius: *radius,
+            }),
+            Collider::Aabb {
+                offset,
+                half_extents,
+            } => shapes.push(Shape::Aabb {
+                center: position + *offset,
+                half_extents: *half_extents,
+            }),
+            Collider::Capsule {
+                offset,
+                half_segment,
+                radius,
+            } => shapes.push(Shape::Capsule {
+                start: position + *offset - *half_segment,
+                end: position + *offset + *half_segment,
+                radius: *radius,
+            }),
+            Collider::Compound { offset, parts } => {
+                for part in parts.iter() {
+                    part.shapes(position + *offset, shapes);
+                }
+            }
+        }
+    }
+
+    /// Check if the colliders of two entities at the given positions intersect
+    pub fn intersects(&self, position: Vec2, other: &Collider, other_position: Vec2) -> bool {
+        let mut shapes = Vec::new();
+        self.shapes(position, &mut shapes);
+        let mut other_shapes = Vec::new();
+        other.shapes(other_position, &mut other_shapes);
+        shapes
+            .iter()
+            .any(|shape| other_shapes.iter().any(|other| shape.intersects(other)))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimeToLive(pub Timer);
@@ -0,0 +1,453 @@
+use crate::{
+    components::{ArenaBounds, BoundsPolicy, EnemyVariant, HitBox, Velocity},
+    constant::{
+        ARENA_HEIGHT, ARENA_WIDTH, CAMERA_SHAKE_FREQUENCY, CAMERA_SHAKE_MAX_OFFSET,
+        CAMERA_SHAKE_MAX_ROLL, CAMERA_SHAKE_TRAUMA_DECAY, ENEMY_BIG_SPRITE_HEIGHT,
+        ENEMY_BIG_SPRITE_WIDTH, ENEMY_BOUNDS_MARGIN, ENEMY_INITIAL_VELOCITY,
+        ENEMY_LASER_COOLDOWN_DURATION, ENEMY_LASER_INITIAL_VELOCITY, ENEMY_LASER_SPRITE_HEIGHT,
+        ENEMY_LASER_SPRITE_WIDTH, ENEMY_LASER_TIME_TO_LIVE_DURATION, ENEMY_MEDIUM_SPRITE_HEIGHT,
+        ENEMY_MEDIUM_SPRITE_WIDTH, ENEMY_SMALL_SPRITE_HEIGHT, ENEMY_SMALL_SPRITE_WIDTH,
+        ENEMY_SPAWN_INTERVAL, HIT_STOP_EXPLOSION_ENEMY_BIG, HIT_STOP_EXPLOSION_SHIP,
+        POOL_CAPACITY_EXPLOSIONS, POOL_CAPACITY_LASERS, SCORE_ENEMY_BIG, SCORE_ENEMY_MEDIUM,
+        SCORE_ENEMY_SMALL, SHIP_BOMBS, SHIP_CORE_RADIUS, SHIP_FOCUS_SPEED_FACTOR,
+        SHIP_INITIAL_MOVE_SPEED, SHIP_LASER_COOLDOWN_DURATION, SHIP_LASER_INITIAL_VELOCITY,
+        SHIP_LASER_SPRITE_HEIGHT, SHIP_LASER_SPRITE_WIDTH, SHIP_LASER_TIME_TO_LIVE_DURATION,
+        SHIP_LIVES, SHIP_RESPAWN_DELAY, SHIP_SPRITE_HEIGHT, SHIP_SPRITE_WIDTH,
+        SHIP_STATE_TRANSITION_DURATION, SPAWN_WEIGHT_ENEMY_BIG, SPAWN_WEIGHT_ENEMY_MEDIUM,
+        SPAWN_WEIGHT_ENEMY_SMALL, TRAUMA_EXPLOSION_ENEMY_BIG, TRAUMA_EXPLOSION_ENEMY_MEDIUM,
+        TRAUMA_EXPLOSION_ENEMY_SMALL, TRAUMA_EXPLOSION_SHIP,
+    },
+};
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path, path::PathBuf, time::Duration};
+
+/// All the numbers that make up the game's balance. Durations are given in seconds, sizes and
+/// velocities are given in arena units. Fields that are left out of a config file take the
+/// value of their section's default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Seed of the random number generator, a random seed is picked when there is none. It is
+    /// only read when the game starts.
+    pub seed: Option<u64>,
+    /// Width and height of the arena, it is only read when the game starts
+    pub arena: (f32, f32),
+    pub ship: ShipConfig,
+    pub enemy: EnemyConfig,
+    pub camera_shake: CameraShakeConfig,
+    pub hit_stop: HitStopConfig,
+    pub pools: PoolConfig,
+    pub accessibility: AccessibilityConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShipConfig {
+    pub move_speed: f32,
+    /// Part of the move speed that is kept while focusing
+    pub focus_speed_factor: f32,
+    pub size: (f32, f32),
+    /// Radius of the circle around the ship's center that has to be hit, it is much smaller than
+    /// the ship
+    pub core_radius: f32,
+    pub state_transition_duration: f32,
+    /// Ships that each player has, a player is out once all of them are destroyed
+    pub lives: u32,
+    /// Time between the destruction of a ship and the arrival of the player's next one
+    pub respawn_delay: f32,
+    /// Bombs that each ship comes with, a bomb clears all the enemies' lasers
+    pub bombs: u32,
+    pub laser: LaserConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnemyConfig {
+    pub velocity: (f32, f32),
+    pub spawn_interval: f32,
+    pub spawn_weights: PerVariant<u8>,
+    pub sizes: PerVariant<(f32, f32)>,
+    /// Points that the player who destroys the enemy is credited with
+    pub scores: PerVariant<u32>,
+    /// How far past the edges of the arena enemies go before they are removed, they come in from
+    /// right above the top edge so it has to be greater than 0
+    pub bounds_margin: f32,
+    pub laser: LaserConfig,
+}
+
+/// Laser fired by a weapon, all the fields but the bounds have to be given since ships and enemies
+/// use different defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LaserConfig {
+    pub size: (f32, f32),
+    pub velocity: (f32, f32),
+    pub cooldown: f32,
+    pub time_to_live: f32,
+    /// What happens to the laser at the edge of the arena, lasers that bounce ricochet until
+    /// their time is up
+    #[serde(default)]
+    pub bounds: BoundsPolicy,
+    #[serde(default)]
+    pub bounds_margin: f32,
+}
+
+impl ShipConfig {
+    pub fn respawn_delay_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.respawn_delay)
+    }
+}
+
+impl LaserConfig {
+    pub fn hit_box(&self) -> HitBox {
+        HitBox(Vec2::new(self.size.0, self.size.1))
+    }
+
+    pub fn velocity(&self) -> Velocity {
+        Velocity(Vec2::new(self.velocity.0, self.velocity.1))
+    }
+
+    pub fn cooldown_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.cooldown)
+    }
+
+    pub fn time_to_live_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.time_to_live)
+    }
+
+    pub fn arena_bounds(&self) -> ArenaBounds {
+        ArenaBounds::new(self.bounds, self.bounds_margin)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraShakeConfig {
+    pub trauma_decay: f32,
+    pub max_offset: f32,
+    pub max_roll: f32,
+    pub frequency: f32,
+    pub trauma_enemy: PerVariant<f32>,
+    pub trauma_ship: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HitStopConfig {
+    pub enemy: PerVariant<f32>,
+    pub ship: f32,
+}
+
+/// Number of hidden entities that are kept to be reused by the next lasers and explosions. More
+/// of them are spawned when a pool runs out, and the ones that do not fit are despawned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolConfig {
+    pub lasers: usize,
+    pub explosions: usize,
+}
+
+/// Options for players who are sensitive to some of the game's effects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessibilityConfig {
+    /// The camera shakes when something explodes
+    pub camera_shake: bool,
+}
+
+/// One value for each variant of enemy, all of them have to be given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PerVariant<T> {
+    pub small: T,
+    pub medium: T,
+    pub big: T,
+}
+
+impl<T> PerVariant<T> {
+    pub fn get(&self, variant: EnemyVariant) -> &T {
+        match variant {
+            EnemyVariant::Small => &self.small,
+            EnemyVariant::Medium => &self.medium,
+            EnemyVariant::Big => &self.big,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            arena: (ARENA_WIDTH, ARENA_HEIGHT),
+            ship: ShipConfig::default(),
+            enemy: EnemyConfig::default(),
+            camera_shake: CameraShakeConfig::default(),
+            hit_stop: HitStopConfig::default(),
+            pools: PoolConfig::default(),
+            accessibility: AccessibilityConfig::default(),
+        }
+    }
+}
+
+impl Default for ShipConfig {
+    fn default() -> Self {
+        Self {
+            move_speed: SHIP_INITIAL_MOVE_SPEED,
+            focus_speed_factor: SHIP_FOCUS_SPEED_FACTOR,
+            size: (SHIP_SPRITE_WIDTH, SHIP_SPRITE_HEIGHT),
+            core_radius: SHIP_CORE_RADIUS,
+            state_transition_duration: SHIP_STATE_TRANSITION_DURATION.as_secs_f32(),
+            lives: SHIP_LIVES,
+            respawn_delay: SHIP_RESPAWN_DELAY.as_secs_f32(),
+            bombs: SHIP_BOMBS,
+            laser: LaserConfig {
+                size: (SHIP_LASER_SPRITE_WIDTH, SHIP_LASER_SPRITE_HEIGHT),
+                velocity: SHIP_LASER_INITIAL_VELOCITY,
+                cooldown: SHIP_LASER_COOLDOWN_DURATION.as_secs_f32(),
+                time_to_live: SHIP_LASER_TIME_TO_LIVE_DURATION.as_secs_f32(),
+                bounds: BoundsPolicy::Despawn,
+                bounds_margin: 0.,
+            },
+        }
+    }
+}
+
+impl Default for EnemyConfig {
+    fn default() -> Self {
+        Self {
+            velocity: ENEMY_INITIAL_VELOCITY,
+            spawn_interval: ENEMY_SPAWN_INTERVAL.as_secs_f32(),
+            spawn_weights: PerVariant {
+                small: SPAWN_WEIGHT_ENEMY_SMALL,
+                medium: SPAWN_WEIGHT_ENEMY_MEDIUM,
+                big: SPAWN_WEIGHT_ENEMY_BIG,
+            },
+            sizes: PerVariant {
+                small: (ENEMY_SMALL_SPRITE_WIDTH, ENEMY_SMALL_SPRITE_HEIGHT),
+                medium: (ENEMY_MEDIUM_SPRITE_WIDTH, ENEMY_MEDIUM_SPRITE_HEIGHT),
+                big: (ENEMY_BIG_SPRITE_WIDTH, ENEMY_BIG_SPRITE_HEIGHT),
+            },
+            scores: PerVariant {
+                small: SCORE_ENEMY_SMALL,
+                medium: SCORE_ENEMY_MEDIUM,
+                big: SCORE_ENEMY_BIG,
+            },
+            bounds_margin: ENEMY_BOUNDS_MARGIN,
+            laser: LaserConfig {
+                size: (ENEMY_LASER_SPRITE_WIDTH, ENEMY_LASER_SPRITE_HEIGHT),
+                velocity: ENEMY_LASER_INITIAL_VELOCITY,
+                cooldown: ENEMY_LASER_COOLDOWN_DURATION.as_secs_f32(),
+                time_to_live: ENEMY_LASER_TIME_TO_LIVE_DURATION.as_secs_f32(),
+                bounds: BoundsPolicy::Despawn,
+                bounds_margin: 0.,
+            },
+        }
+    }
+}
+
+impl Default for CameraShakeConfig {
+    fn default() -> Self {
+        Self {
+            trauma_decay: CAMERA_SHAKE_TRAUMA_DECAY,
+            max_offset: CAMERA_SHAKE_MAX_OFFSET,
+            max_roll: CAMERA_SHAKE_MAX_ROLL,
+            frequency: CAMERA_SHAKE_FREQUENCY,
+            trauma_enemy: PerVariant {
+                small: TRAUMA_EXPLOSION_ENEMY_SMALL,
+                medium: TRAUMA_EXPLOSION_ENEMY_MEDIUM,
+                big: TRAUMA_EXPLOSION_ENEMY_BIG,
+            },
+            trauma_ship: TRAUMA_EXPLOSION_SHIP,
+        }
+    }
+}
+
+impl Default for HitStopConfig {
+    fn default() -> Self {
+        Self {
+            enemy: PerVariant {
+                small: 0.,
+                medium: 0.,
+                big: HIT_STOP_EXPLOSION_ENEMY_BIG.as_secs_f32(),
+            },
+            ship: HIT_STOP_EXPLOSION_SHIP.as_secs_f32(),
+        }
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            lasers: POOL_CAPACITY_LASERS,
+            explosions: POOL_CAPACITY_EXPLOSIONS,
+        }
+    }
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self { camera_shake: true }
+    }
+}
+
+/// Errors that make a config file unusable
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(ron::Error),
+    Invalid {
+        field: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+            ConfigError::Parse(err) => write!(f, "invalid config: {}", err),
+            ConfigError::Invalid { field, reason } => write!(f, "`{}` {}", field, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl GameConfig {
+    /// Read and validate a config file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content =
+            fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        Self::parse(&content)
+    }
+
+    /// Parse and validate the content of a config file
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
+        let config: Self = ron::de::from_str(content).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that all the values can be used by the game
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        positive_size("arena", self.arena)?;
+
+        positive("ship.move_speed", self.ship.move_speed)?;
+        positive("ship.focus_speed_factor", self.ship.focus_speed_factor)?;
+        positive_size("ship.size", self.ship.size)?;
+        positive("ship.core_radius", self.ship.core_radius)?;
+        positive(
+            "ship.state_transition_duration",
+            self.ship.state_transition_duration,
+        )?;
+        if self.ship.lives == 0 {
+            return Err(ConfigError::Invalid {
+                field: "ship.lives",
+                reason: "must be greater than 0",
+            });
+        }
+        not_negative("ship.respawn_delay", self.ship.respawn_delay)?;
+        validate_laser(
+            "ship.laser.size",
+            "ship.laser.cooldown",
+            "ship.laser.time_to_live",
+            "ship.laser.bounds_margin",
+            &self.ship.laser,
+        )?;
+
+        positive("enemy.spawn_interval", self.enemy.spawn_interval)?;
+        let spawn_weights = &self.enemy.spawn_weights;
+        if spawn_weights.small == 0 && spawn_weights.medium == 0 && spawn_weights.big == 0 {
+            return Err(ConfigError::Invalid {
+                field: "enemy.spawn_weights",
+                reason: "must have at least one weight greater than 0",
+            });
+        }
+        positive_size("enemy.sizes.small", self.enemy.sizes.small)?;
+        positive_size("enemy.sizes.medium", self.enemy.sizes.medium)?;
+        positive_size("enemy.sizes.big", self.enemy.sizes.big)?;
+        positive("enemy.bounds_margin", self.enemy.bounds_margin)?;
+        validate_laser(
+            "enemy.laser.size",
+            "enemy.laser.cooldown",
+            "enemy.laser.time_to_live",
+            "enemy.laser.bounds_margin",
+            &self.enemy.laser,
+        )?;
+
+        not_negative("camera_shake.trauma_decay", self.camera_shake.trauma_decay)?;
+        not_negative("camera_shake.max_offset", self.camera_shake.max_offset)?;
+        not_negative("camera_shake.max_roll", self.camera_shake.max_roll)?;
+        not_negative("camera_shake.frequency", self.camera_shake.frequency)?;
+        let trauma_enemy = &self.camera_shake.trauma_enemy;
+        unit("camera_shake.trauma_enemy.small", trauma_enemy.small)?;
+        unit("camera_shake.trauma_enemy.medium", trauma_enemy.medium)?;
+        unit("camera_shake.trauma_enemy.big", trauma_enemy.big)?;
+        unit("camera_shake.trauma_ship", self.camera_shake.trauma_ship)?;
+
+        not_negative("hit_stop.enemy.small", self.hit_stop.enemy.small)?;
+        not_negative("hit_stop.enemy.medium", self.hit_stop.enemy.medium)?;
+        not_negative("hit_stop.enemy.big", self.hit_stop.enemy.big)?;
+        not_negative("hit_stop.ship", self.hit_stop.ship)?;
+        Ok(())
+    }
+}
+
+fn validate_laser(
+    size_field: &'static str,
+    cooldown_field: &'static str,
+    time_to_live_field: &'static str,
+    bounds_margin_field: &'static str,
+    laser: &LaserConfig,
+) -> Result<(), ConfigError> {
+    positive_size(size_field, laser.size)?;
+    positive(cooldown_field, laser.cooldown)?;
+    positive(time_to_live_field, laser.time_to_live)?;
+    not_negative(bounds_margin_field, laser.bounds_margin)
+}
+
+fn positive(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value > 0. && value.is_finite() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: "must be greater than 0",
+        })
+    }
+}
+
+fn positive_size(field: &'static str, (width, height): (f32, f32)) -> Result<(), ConfigError> {
+    if width > 0. && height > 0. && width.is_finite() && height.is_finite() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: "must have a width and a height greater than 0",
+        })
+    }
+}
+
+fn not_negative(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value >= 0. && value.is_finite() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: "must not be negative",
+        })
+    }
+}
+
+fn unit(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if (0. ..=1.).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: "must be between 0 and 1",
+        })
+    }
+}
@@ -1,14 +1,88 @@
+use bevy::{input::keyboard::KeyCode, render::color::Color};
 use std::time::Duration;
 
 pub const ARENA_WIDTH: f32 = 180.;
 pub const ARENA_HEIGHT: f32 = 240.;
 pub const ARENA_SCALE: f32 = 2.0;
-pub const ANIMATION_INTERVAL: Duration = Duration::from_millis(200);
+pub const LETTERBOX_Z: f32 = 100.;
+pub const LETTERBOX_BAR_SIZE: f32 = 10_000.;
+pub const FULLSCREEN_KEY: KeyCode = KeyCode::F11;
+pub const MAX_PLAYERS: usize = 2;
+pub const COLLISION_GRID_CELL_SIZE: f32 = 32.;
+pub const POOL_CAPACITY_LASERS: usize = 256;
+pub const POOL_CAPACITY_EXPLOSIONS: usize = 32;
+pub const FIXED_TIMESTEP_RATE: f32 = 60.;
+pub const MAX_FIXED_STEPS_PER_FRAME: u32 = 5;
+pub const STAGE_INTERPOLATE: &str = "interpolate";
+pub const REPLAY_MAGIC: &[u8; 4] = b"SSRP";
+pub const REPLAY_VERSION: u16 = 4;
+pub const NET_MAGIC: &[u8; 4] = b"SSNP";
+pub const NET_PROTOCOL_VERSION: u16 = 1;
+pub const NET_MAX_PREDICTION_TICKS: usize = 8;
+pub const NET_JOIN_INTERVAL: Duration = Duration::from_millis(200);
+pub const NET_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(60);
+pub const NET_DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const CONFIG_PATH: &str = "config.ron";
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+pub const SETTINGS_PATH: &str = "settings.ron";
+pub const REBIND_KEY: KeyCode = KeyCode::F1;
+pub const REBIND_CONFIRM_KEY: KeyCode = KeyCode::Return;
+pub const REBIND_FONT_PATH: &str = "fonts/DejaVuSansMono.ttf";
+pub const REBIND_FONT_SIZE: f32 = 16.;
+pub const REBIND_LINE_HEIGHT: f32 = 20.;
+pub const REBIND_MARGIN: f32 = 10.;
+pub const GAMEPAD_STICK_DEADZONE: f32 = 0.2;
+pub const POINTER_FOLLOW_DURATION: f32 = 0.1;
+pub const ATLAS_MANIFEST_PATH: &str = "atlases.ron";
+pub const ANIMATION_MANIFEST_PATH: &str = "animations.ron";
+
+// Clips that the game plays, they are declared in the atlas manifest
+pub const ANIMATION_CLIP_ENEMY_SMALL: &str = "enemy_small";
+pub const ANIMATION_CLIP_ENEMY_MEDIUM: &str = "enemy_medium";
+pub const ANIMATION_CLIP_ENEMY_BIG: &str = "enemy_big";
+pub const ANIMATION_CLIP_SHIP_LASER: &str = "ship_laser";
+pub const ANIMATION_CLIP_ENEMY_LASER: &str = "enemy_laser";
+pub const ANIMATION_CLIP_EXPLOSION: &str = "explosion";
+pub const ANIMATION_CLIPS_PLAYED: [&str; 6] = [
+    ANIMATION_CLIP_ENEMY_SMALL,
+    ANIMATION_CLIP_ENEMY_MEDIUM,
+    ANIMATION_CLIP_ENEMY_BIG,
+    ANIMATION_CLIP_SHIP_LASER,
+    ANIMATION_CLIP_ENEMY_LASER,
+    ANIMATION_CLIP_EXPLOSION,
+];
+
+pub const LAYERED_ANIMATION_SHIP: &str = "ship";
+
+// Draw order of the different kinds of entities, entities with higher z are drawn in front
+pub const BACKGROUND_DESERT_Z: f32 = 0.;
+pub const BACKGROUND_CLOUDS_Z: f32 = 1.;
+pub const BACKGROUND_CLOUDS_TRANSPARENT_Z: f32 = 2.;
+pub const ENEMY_Z: f32 = 10.;
+pub const LASER_Z: f32 = 20.;
+pub const SHIP_Z: f32 = 30.;
+pub const EXPLOSION_Z: f32 = 40.;
+
+pub const BACKGROUND_SPRITE_WIDTH: f32 = 256.;
+pub const BACKGROUND_DESERT_SPRITE_HEIGHT: f32 = 272.;
+pub const BACKGROUND_DESERT_SCROLL_SPEED: f32 = 10.;
+pub const BACKGROUND_CLOUDS_SPRITE_HEIGHT: f32 = 103.;
+pub const BACKGROUND_CLOUDS_SCROLL_SPEED: f32 = 20.;
+pub const BACKGROUND_CLOUDS_TRANSPARENT_SPRITE_HEIGHT: f32 = 103.;
+pub const BACKGROUND_CLOUDS_TRANSPARENT_SCROLL_SPEED: f32 = 35.;
 
 pub const SHIP_INITIAL_MOVE_SPEED: f32 = 100.;
+pub const SHIP_FOCUS_SPEED_FACTOR: f32 = 0.5;
 pub const SHIP_SPRITE_WIDTH: f32 = 16.;
 pub const SHIP_SPRITE_HEIGHT: f32 = 24.;
+pub const SHIP_CORE_RADIUS: f32 = 3.;
 pub const SHIP_STATE_TRANSITION_DURATION: Duration = Duration::from_millis(100);
+pub const SHIP_LIVES: u32 = 1;
+pub const SHIP_RESPAWN_DELAY: Duration = Duration::from_millis(1500);
+pub const SHIP_BOMBS: u32 = 2;
+pub const SHIP_SPAWN_TRANSLATIONS: [(f32, f32); MAX_PLAYERS] = [(0., 0.), (32., 0.)];
+pub const SHIP_TINTS: [Color; MAX_PLAYERS] = [Color::WHITE, Color::rgb(0.6, 0.8, 1.)];
 
 pub const SHIP_LASER_SPRITE_WIDTH: f32 = 5.;
 pub const SHIP_LASER_SPRITE_HEIGHT: f32 = 13.;
@@ -17,10 +91,14 @@ pub const SHIP_LASER_COOLDOWN_DURATION: Duration = Duration::from_millis(500);
 pub const SHIP_LASER_TIME_TO_LIVE_DURATION: Duration = Duration::from_secs(2);
 
 pub const ENEMY_INITIAL_VELOCITY: (f32, f32) = (0., -30.);
+pub const ENEMY_BOUNDS_MARGIN: f32 = 2.;
 pub const ENEMY_SPAWN_INTERVAL: Duration = Duration::from_millis(1500);
 pub const SPAWN_WEIGHT_ENEMY_SMALL: u8 = 8;
 pub const SPAWN_WEIGHT_ENEMY_MEDIUM: u8 = 4;
 pub const SPAWN_WEIGHT_ENEMY_BIG: u8 = 2;
+pub const SCORE_ENEMY_SMALL: u32 = 100;
+pub const SCORE_ENEMY_MEDIUM: u32 = 200;
+pub const SCORE_ENEMY_BIG: u32 = 500;
 
 pub const ENEMY_LASER_SPRITE_WIDTH: f32 = 5.;
 pub const ENEMY_LASER_SPRITE_HEIGHT: f32 = 5.;
@@ -39,3 +117,15 @@ pub const ENEMY_SMALL_SPRITE_HEIGHT: f32 = 16.;
 
 pub const EXPLOSION_SPRITE_WIDTH: f32 = 16.;
 pub const EXPLOSION_SPRITE_HEIGHT: f32 = 16.;
+
+pub const CAMERA_SHAKE_TRAUMA_DECAY: f32 = 1.5;
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 4.;
+pub const CAMERA_SHAKE_MAX_ROLL: f32 = 0.05;
+pub const CAMERA_SHAKE_FREQUENCY: f32 = 25.;
+pub const TRAUMA_EXPLOSION_ENEMY_SMALL: f32 = 0.2;
+pub const TRAUMA_EXPLOSION_ENEMY_MEDIUM: f32 = 0.35;
+pub const TRAUMA_EXPLOSION_ENEMY_BIG: f32 = 0.5;
+pub const TRAUMA_EXPLOSION_SHIP: f32 = 0.9;
+
+pub const HIT_STOP_EXPLOSION_ENEMY_BIG: Duration = Duration::from_millis(50);
+pub const HIT_STOP_EXPLOSION_SHIP: Duration = Duration::from_millis(150);
@@ -0,0 +1,77 @@
+use crate::{
+    components::BackgroundLayer,
+    constant::{
+        BACKGROUND_CLOUDS_SCROLL_SPEED, BACKGROUND_CLOUDS_SPRITE_HEIGHT,
+        BACKGROUND_CLOUDS_TRANSPARENT_SCROLL_SPEED, BACKGROUND_CLOUDS_TRANSPARENT_SPRITE_HEIGHT,
+        BACKGROUND_CLOUDS_TRANSPARENT_Z, BACKGROUND_CLOUDS_Z, BACKGROUND_DESERT_SCROLL_SPEED,
+        BACKGROUND_DESERT_SPRITE_HEIGHT, BACKGROUND_DESERT_Z, BACKGROUND_SPRITE_WIDTH,
+    },
+    resource::Arena,
+};
+use bevy::prelude::*;
+
+#[derive(Bundle)]
+pub struct BackgroundLayerComponents {
+    pub background_layer: BackgroundLayer,
+}
+
+/// Add the tiles of all the background layers, from the farthest layer to the nearest one
+pub fn initialize_background(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    arena: Res<Arena>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let layers = [
+        (
+            "spritesheets/desert-backgorund.png",
+            BACKGROUND_DESERT_SPRITE_HEIGHT,
+            BACKGROUND_DESERT_SCROLL_SPEED,
+            BACKGROUND_DESERT_Z,
+        ),
+        (
+            "spritesheets/clouds.png",
+            BACKGROUND_CLOUDS_SPRITE_HEIGHT,
+            BACKGROUND_CLOUDS_SCROLL_SPEED,
+            BACKGROUND_CLOUDS_Z,
+        ),
+        (
+            "spritesheets/clouds-transparent.png",
+            BACKGROUND_CLOUDS_TRANSPARENT_SPRITE_HEIGHT,
+            BACKGROUND_CLOUDS_TRANSPARENT_SCROLL_SPEED,
+            BACKGROUND_CLOUDS_TRANSPARENT_Z,
+        ),
+    ];
+
+    for (texture_path, tile_height, scroll_speed, z) in layers.iter() {
+        let material = materials.add(asset_server.load(*texture_path).into());
+
+        // Enough tiles to cover the arena, plus one that is waiting above the arena. Arenas that
+        // are wider than a tile get columns of tiles side by side.
+        let tile_count = (arena.height / tile_height).ceil() as u32 + 1;
+        let column_count = (arena.width / BACKGROUND_SPRITE_WIDTH).ceil().max(1.) as u32;
+        for column_idx in 0..column_count {
+            let tile_x =
+                (column_idx as f32 - (column_count - 1) as f32 / 2.) * BACKGROUND_SPRITE_WIDTH;
+            for tile_idx in 0..tile_count {
+                commands
+                    .spawn(SpriteComponents {
+                        material: material.clone(),
+                        transform: Transform::from_translation(Vec3::new(
+                            tile_x,
+                            (-arena.height + tile_height) / 2. + tile_idx as f32 * tile_height,
+                            *z,
+                        )),
+                        ..Default::default()
+                    })
+                    .with_bundle(BackgroundLayerComponents {
+                        background_layer: BackgroundLayer {
+                            scroll_speed: *scroll_speed,
+                            tile_height: *tile_height,
+                            tile_count,
+                        },
+                    });
+            }
+        }
+    }
+}
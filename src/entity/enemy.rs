@@ -1,15 +1,16 @@
 use crate::{
-    components::{Animation, Enemy, EnemyVariant, HitBox, Velocity, Weapon},
+    components::{
+        Animation, ArenaBounds, BoundsPolicy, Collider, CollisionLayer, Enemy, EnemyVariant,
+        HitBox, PreviousTranslation, SheetSprite, Velocity, Weapon,
+    },
+    config::GameConfig,
     constant::{
-        ANIMATION_INTERVAL, ENEMY_BIG_SPRITE_HEIGHT, ENEMY_BIG_SPRITE_WIDTH,
-        ENEMY_INITIAL_VELOCITY, ENEMY_LASER_COOLDOWN_DURATION, ENEMY_LASER_INITIAL_VELOCITY,
-        ENEMY_LASER_SPRITE_HEIGHT, ENEMY_LASER_SPRITE_WIDTH, ENEMY_LASER_TIME_TO_LIVE_DURATION,
-        ENEMY_MEDIUM_SPRITE_HEIGHT, ENEMY_MEDIUM_SPRITE_WIDTH, ENEMY_SMALL_SPRITE_HEIGHT,
-        ENEMY_SMALL_SPRITE_WIDTH,
+        ANIMATION_CLIP_ENEMY_BIG, ANIMATION_CLIP_ENEMY_LASER, ANIMATION_CLIP_ENEMY_MEDIUM,
+        ANIMATION_CLIP_ENEMY_SMALL, ENEMY_Z,
     },
     entity::WeaponComponents,
     events::SpawnEnemyEvent,
-    resource::{EventReaders, TextureAtlasHandles},
+    resource::EventReaders,
 };
 use bevy::prelude::*;
 
@@ -18,76 +19,65 @@ pub struct EnemyComponents {
     pub enemy: Enemy,
     pub velocity: Velocity,
     pub hit_box: HitBox,
+    pub collider: Collider,
+    pub collision_layer: CollisionLayer,
+    pub arena_bounds: ArenaBounds,
     pub animation: Animation,
+    pub previous_translation: PreviousTranslation,
+    pub sheet_sprite: SheetSprite,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
 }
 
 pub fn spawn_enemy(
     mut commands: Commands,
     spawn_enemy_events: Res<Events<SpawnEnemyEvent>>,
-    texture_atlas_handles: Res<TextureAtlasHandles>,
+    config: Res<GameConfig>,
     mut event_readers: ResMut<EventReaders>,
 ) {
     for evt in event_readers.spawn_enemy.iter(&spawn_enemy_events) {
-        let (hit_box_vec2, texture_atlas) = match evt.enemy_variant {
-            EnemyVariant::Small => (
-                Vec2::new(ENEMY_SMALL_SPRITE_WIDTH, ENEMY_SMALL_SPRITE_HEIGHT),
-                texture_atlas_handles.enemy_small.clone(),
-            ),
-            EnemyVariant::Medium => (
-                Vec2::new(ENEMY_MEDIUM_SPRITE_WIDTH, ENEMY_MEDIUM_SPRITE_HEIGHT),
-                texture_atlas_handles.enemy_medium.clone(),
-            ),
-            EnemyVariant::Big => (
-                Vec2::new(ENEMY_BIG_SPRITE_WIDTH, ENEMY_BIG_SPRITE_HEIGHT),
-                texture_atlas_handles.enemy_big.clone(),
-            ),
+        let clip = match evt.enemy_variant {
+            EnemyVariant::Small => ANIMATION_CLIP_ENEMY_SMALL,
+            EnemyVariant::Medium => ANIMATION_CLIP_ENEMY_MEDIUM,
+            EnemyVariant::Big => ANIMATION_CLIP_ENEMY_BIG,
         };
+        let (enemy_width, enemy_height) = *config.enemy.sizes.get(evt.enemy_variant);
+        let hit_box_vec2 = Vec2::new(enemy_width, enemy_height);
+        let enemy_translation = evt.enemy_translation.truncate().extend(ENEMY_Z);
 
-        commands
-            .spawn(SpriteSheetComponents {
-                texture_atlas,
-                transform: Transform {
-                    translation: evt.enemy_translation,
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .with_bundle(EnemyComponents {
-                enemy: Enemy {
-                    variant: evt.enemy_variant,
-                },
-                hit_box: HitBox(hit_box_vec2),
-                velocity: Velocity(Vec2::new(
-                    ENEMY_INITIAL_VELOCITY.0,
-                    ENEMY_INITIAL_VELOCITY.1,
-                )),
-                animation: Animation {
-                    idx_delta: 1,
-                    sprite_count: 2,
-                    timer: Timer::new(ANIMATION_INTERVAL, true),
-                },
-            });
+        commands.spawn(EnemyComponents {
+            enemy: Enemy {
+                variant: evt.enemy_variant,
+            },
+            hit_box: HitBox(hit_box_vec2),
+            collider: Collider::aabb(hit_box_vec2),
+            collision_layer: CollisionLayer::enemy(),
+            arena_bounds: ArenaBounds::new(BoundsPolicy::Despawn, config.enemy.bounds_margin),
+            velocity: Velocity(Vec2::new(config.enemy.velocity.0, config.enemy.velocity.1)),
+            animation: Animation::new(clip),
+            previous_translation: PreviousTranslation(enemy_translation),
+            sheet_sprite: SheetSprite::default(),
+            transform: Transform::from_translation(enemy_translation),
+            global_transform: Default::default(),
+        });
 
         match evt.enemy_variant {
             EnemyVariant::Medium | EnemyVariant::Big => {
                 commands.with_children(|parent| {
+                    let laser_config = &config.enemy.laser;
                     let mut weapon_cooldown_timer =
-                        Timer::new(ENEMY_LASER_COOLDOWN_DURATION, false);
-                    weapon_cooldown_timer.tick(ENEMY_LASER_COOLDOWN_DURATION.as_secs_f32());
+                        Timer::new(laser_config.cooldown_duration(), false);
+                    weapon_cooldown_timer.tick(laser_config.cooldown);
 
                     parent.spawn(WeaponComponents {
                         weapon: Weapon {
                             cooldown_timer: weapon_cooldown_timer,
-                            laser_velocity: Velocity(Vec2::new(
-                                ENEMY_LASER_INITIAL_VELOCITY.0,
-                                ENEMY_LASER_INITIAL_VELOCITY.1,
-                            )),
-                            laser_hit_box: HitBox(Vec2::new(
-                                ENEMY_LASER_SPRITE_WIDTH,
-                                ENEMY_LASER_SPRITE_HEIGHT,
-                            )),
-                            laser_time_to_live_duration: ENEMY_LASER_TIME_TO_LIVE_DURATION,
-                            laser_initial_sprite_idx: 0,
+                            laser_velocity: laser_config.velocity(),
+                            laser_hit_box: laser_config.hit_box(),
+                            laser_time_to_live_duration: laser_config.time_to_live_duration(),
+                            laser_animation_clip: ANIMATION_CLIP_ENEMY_LASER.to_string(),
+                            laser_collision_layer: CollisionLayer::enemy_laser(),
+                            laser_bounds: laser_config.arena_bounds(),
                         },
                         transform: Transform {
                             translation: -hit_box_vec2.y() * Vec3::unit_y(),
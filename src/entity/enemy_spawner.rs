@@ -1,11 +1,9 @@
 use crate::{
     components::{EnemySpawner, EnemyVariant},
-    constant::{
-        ENEMY_SPAWN_INTERVAL, SPAWN_WEIGHT_ENEMY_BIG, SPAWN_WEIGHT_ENEMY_MEDIUM,
-        SPAWN_WEIGHT_ENEMY_SMALL,
-    },
+    config::GameConfig,
 };
 use bevy::prelude::*;
+use std::time::Duration;
 
 #[derive(Bundle)]
 pub struct EnemySpawnerComponents {
@@ -13,15 +11,19 @@ pub struct EnemySpawnerComponents {
 }
 
 /// Create a new enemy spawner
-pub fn initialize_enemy_spawner(mut commands: Commands) {
+pub fn initialize_enemy_spawner(mut commands: Commands, config: Res<GameConfig>) {
     commands.spawn(EnemySpawnerComponents {
         enemy_spawner: EnemySpawner {
-            timer: Timer::new(ENEMY_SPAWN_INTERVAL, true),
-            weights: vec![
-                (EnemyVariant::Small, SPAWN_WEIGHT_ENEMY_SMALL),
-                (EnemyVariant::Medium, SPAWN_WEIGHT_ENEMY_MEDIUM),
-                (EnemyVariant::Big, SPAWN_WEIGHT_ENEMY_BIG),
-            ],
+            timer: Timer::new(Duration::from_secs_f32(config.enemy.spawn_interval), true),
+            weights: spawn_weights(&config),
         },
     });
 }
+
+/// The spawn weight of each variant of enemy
+pub fn spawn_weights(config: &GameConfig) -> Vec<(EnemyVariant, u8)> {
+    [EnemyVariant::Small, EnemyVariant::Medium, EnemyVariant::Big]
+        .iter()
+        .map(|&variant| (variant, *config.enemy.spawn_weights.get(variant)))
+        .collect()
+}
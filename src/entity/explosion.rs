@@ -1,42 +1,57 @@
 use crate::{
-    components::{Animation, Explosion, TimeToLive},
-    constant::ANIMATION_INTERVAL,
+    components::{Animation, Explosion, Pooled, SheetSprite},
+    constant::{ANIMATION_CLIP_EXPLOSION, EXPLOSION_Z},
     events::SpawnExplosionEvent,
-    resource::{EventReaders, TextureAtlasHandles},
+    resource::{EntityPool, EntityPools, EventReaders},
 };
 use bevy::prelude::*;
 
 #[derive(Bundle)]
 pub struct ExplosionComponents {
     pub explosion: Explosion,
-    pub time_to_live: TimeToLive,
     pub animation: Animation,
+    pub sheet_sprite: SheetSprite,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
 }
 
+/// The components that an explosion loses when it goes back to its pool
+type PooledExplosionComponents = (Explosion, Animation, SheetSprite);
+
+/// Spawn the explosions, a free explosion from the pool is reused when there is one
 pub fn spawn_explosion(
     mut commands: Commands,
     spawn_explosion_events: Res<Events<SpawnExplosionEvent>>,
-    texture_atlas_handles: Res<TextureAtlasHandles>,
     mut event_readers: ResMut<EventReaders>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_free: Query<Without<Explosion, &Pooled>>,
 ) {
     for evt in event_readers.spawn_explosion.iter(&spawn_explosion_events) {
-        commands
-            .spawn(SpriteSheetComponents {
-                texture_atlas: texture_atlas_handles.explosion.clone(),
-                transform: Transform {
-                    translation: evt.explosion_translation,
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .with_bundle(ExplosionComponents {
-                explosion: Explosion,
-                time_to_live: TimeToLive(Timer::new(evt.explosion_time_to_live_duration, false)),
-                animation: Animation {
-                    idx_delta: 1,
-                    sprite_count: 2,
-                    timer: Timer::new(ANIMATION_INTERVAL, true),
-                },
-            });
+        let explosion_components = ExplosionComponents {
+            explosion: Explosion,
+            animation: Animation::new(ANIMATION_CLIP_EXPLOSION),
+            sheet_sprite: SheetSprite::default(),
+            transform: Transform::from_translation(
+                evt.explosion_translation.truncate().extend(EXPLOSION_Z),
+            ),
+            global_transform: Default::default(),
+        };
+        match entity_pools
+            .explosions
+            .acquire(|entity| query_free.get(entity).is_ok())
+        {
+            Some(entity) => commands.insert(entity, explosion_components),
+            None => commands.spawn(explosion_components).with(Pooled),
+        };
+    }
+}
+
+/// Take an explosion out of the game world, it waits in the pool to be reused or is despawned when
+/// the pool is full
+pub fn release_explosion(commands: &mut Commands, explosion_pool: &mut EntityPool, entity: Entity) {
+    if explosion_pool.release(entity) {
+        commands.remove::<PooledExplosionComponents>(entity);
+    } else {
+        commands.despawn(entity);
     }
 }
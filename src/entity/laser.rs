@@ -1,8 +1,11 @@
 use crate::{
-    components::{Animation, HitBox, Laser, TimeToLive, Velocity},
-    constant::ANIMATION_INTERVAL,
+    components::{
+        Animation, ArenaBounds, Collider, CollisionLayer, ContinuousCollision, HitBox, Laser,
+        Player, Pooled, PreviousTranslation, SheetSprite, TimeToLive, Velocity,
+    },
+    constant::LASER_Z,
     events::SpawnLaserEvent,
-    resource::{EventReaders, TextureAtlasHandles},
+    resource::{EntityPool, EntityPools, EventReaders},
 };
 use bevy::prelude::*;
 
@@ -12,38 +15,79 @@ pub struct LaserComponents {
     pub time_to_live: TimeToLive,
     pub velocity: Velocity,
     pub hit_box: HitBox,
+    pub collider: Collider,
+    pub collision_layer: CollisionLayer,
+    pub arena_bounds: ArenaBounds,
+    pub continuous_collision: ContinuousCollision,
     pub animation: Animation,
+    pub previous_translation: PreviousTranslation,
+    pub sheet_sprite: SheetSprite,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
 }
 
+/// The components that a laser loses when it goes back to its pool, the sprite and the position
+/// are kept for the next laser
+type PooledLaserComponents = (
+    Laser,
+    TimeToLive,
+    Velocity,
+    HitBox,
+    Collider,
+    CollisionLayer,
+    ArenaBounds,
+    ContinuousCollision,
+    Animation,
+    PreviousTranslation,
+    SheetSprite,
+);
+
+/// Spawn the lasers, a free laser from the pool is reused when there is one
 pub fn spawn_laser(
     mut commands: Commands,
     spawn_laser_events: Res<Events<SpawnLaserEvent>>,
-    texture_atlas_handles: Res<TextureAtlasHandles>,
     mut event_readers: ResMut<EventReaders>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_player: Query<&Player>,
+    query_free: Query<Without<Laser, &Pooled>>,
 ) {
     for evt in event_readers.spawn_laser.iter(&spawn_laser_events) {
-        commands
-            .spawn(SpriteSheetComponents {
-                texture_atlas: texture_atlas_handles.laser_bolts.clone(),
-                transform: Transform {
-                    translation: evt.laser_translation,
-                    ..Default::default()
-                },
-                sprite: TextureAtlasSprite::new(evt.laser_initial_sprite_idx),
-                ..Default::default()
-            })
-            .with_bundle(LaserComponents {
-                laser: Laser {
-                    source: evt.laser_source,
-                },
-                time_to_live: TimeToLive(Timer::new(evt.laser_time_to_live_duration, false)),
-                hit_box: evt.laser_hit_box,
-                velocity: evt.laser_velocity,
-                animation: Animation {
-                    idx_delta: 2,
-                    sprite_count: 4,
-                    timer: Timer::new(ANIMATION_INTERVAL, true),
-                },
-            });
+        let laser_translation = evt.laser_translation.truncate().extend(LASER_Z);
+        let laser_components = LaserComponents {
+            laser: Laser {
+                source: evt.laser_source,
+                // Looked up now so the kill is still credited after the source ship is gone
+                player: query_player.get(evt.laser_source).ok().copied(),
+            },
+            time_to_live: TimeToLive(Timer::new(evt.laser_time_to_live_duration, false)),
+            hit_box: evt.laser_hit_box,
+            collider: Collider::capsule_in(evt.laser_hit_box.0),
+            collision_layer: evt.laser_collision_layer,
+            arena_bounds: evt.laser_bounds,
+            continuous_collision: ContinuousCollision,
+            velocity: evt.laser_velocity,
+            animation: Animation::new(&evt.laser_animation_clip),
+            previous_translation: PreviousTranslation(laser_translation),
+            sheet_sprite: SheetSprite::default(),
+            transform: Transform::from_translation(laser_translation),
+            global_transform: Default::default(),
+        };
+        match entity_pools
+            .lasers
+            .acquire(|entity| query_free.get(entity).is_ok())
+        {
+            Some(entity) => commands.insert(entity, laser_components),
+            None => commands.spawn(laser_components).with(Pooled),
+        };
+    }
+}
+
+/// Take a laser out of the game world, it waits in the pool to be reused or is despawned when the
+/// pool is full
+pub fn release_laser(commands: &mut Commands, laser_pool: &mut EntityPool, entity: Entity) {
+    if laser_pool.release(entity) {
+        commands.remove::<PooledLaserComponents>(entity);
+    } else {
+        commands.despawn(entity);
     }
 }
@@ -1,3 +1,4 @@
+mod background;
 mod enemy;
 mod enemy_spawner;
 mod explosion;
@@ -5,20 +6,102 @@ mod laser;
 mod ship;
 mod weapon;
 
+pub use background::*;
 pub use enemy::*;
-pub use weapon::*;
 pub use enemy_spawner::*;
 pub use explosion::*;
 pub use laser::*;
 pub use ship::*;
+pub use weapon::*;
 
-use crate::constant::ARENA_SCALE;
+use crate::{
+    components::{CameraShake, LetterboxBar, RebindingLine},
+    config::GameConfig,
+    constant::{
+        LETTERBOX_BAR_SIZE, LETTERBOX_Z, REBIND_FONT_PATH, REBIND_FONT_SIZE, REBIND_LINE_HEIGHT,
+        REBIND_MARGIN,
+    },
+    resource::{Arena, ArenaView, Rebinding},
+};
 use bevy::prelude::*;
 
 /// Create a camera
-pub fn initialize_camera(mut commands: Commands) {
-    commands.spawn(Camera2dComponents {
-        transform: Transform::from_scale(Vec3::splat(1. / ARENA_SCALE)),
-        ..Default::default()
+pub fn initialize_camera(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    arena_view: Res<ArenaView>,
+) {
+    // Keep the camera's default position so entities with positive z are still in its view
+    let mut camera = Camera2dComponents::default();
+    camera.transform.scale = Vec3::splat(1. / arena_view.scale);
+    commands.spawn(camera).with(CameraShake {
+        trauma: 0.,
+        trauma_decay: config.camera_shake.trauma_decay,
+        max_offset: config.camera_shake.max_offset,
+        max_roll: config.camera_shake.max_roll,
+        frequency: config.camera_shake.frequency,
     });
 }
+
+/// Add the black bars on every side of the arena. They are much larger than any window, so they
+/// cover everything out of the arena whatever the shape of the window and the shake of the camera.
+pub fn initialize_letterbox(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let material = materials.add(Color::BLACK.into());
+    let half_arena = arena.size() / 2.;
+    let half_bar = LETTERBOX_BAR_SIZE / 2.;
+    // The bars on the sides are tall enough to cover the corners
+    let tall = Vec2::new(LETTERBOX_BAR_SIZE, arena.height + 2. * LETTERBOX_BAR_SIZE);
+    let wide = Vec2::new(arena.width, LETTERBOX_BAR_SIZE);
+    let bars = [
+        (Vec2::new(-half_arena.x() - half_bar, 0.), tall),
+        (Vec2::new(half_arena.x() + half_bar, 0.), tall),
+        (Vec2::new(0., -half_arena.y() - half_bar), wide),
+        (Vec2::new(0., half_arena.y() + half_bar), wide),
+    ];
+    for (center, size) in bars.iter() {
+        commands
+            .spawn(SpriteComponents {
+                material: material.clone(),
+                sprite: Sprite::new(*size),
+                transform: Transform::from_translation(center.extend(LETTERBOX_Z)),
+                ..Default::default()
+            })
+            .with(LetterboxBar);
+    }
+}
+
+/// Add a UI camera and the lines of text of the rebinding screen, the lines stay empty until the
+/// actions are rebound
+pub fn initialize_rebinding_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(UiCameraComponents::default());
+    let font = asset_server.load(REBIND_FONT_PATH);
+    for line in 0..Rebinding::LINE_COUNT {
+        commands
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        top: Val::Px(REBIND_MARGIN + line as f32 * REBIND_LINE_HEIGHT),
+                        left: Val::Px(REBIND_MARGIN),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text {
+                    value: String::new(),
+                    font: font.clone(),
+                    style: TextStyle {
+                        font_size: REBIND_FONT_SIZE,
+                        color: Color::WHITE,
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            })
+            .with(RebindingLine(line));
+    }
+}
@@ -1,70 +1,111 @@
 use crate::{
+    animation::LayeredAnimations,
     components::{
-        Animation, ConstrainedToArena, HitBox, Ship, ShipAnimationState, Velocity, Weapon,
+        ArenaBounds, BoundsPolicy, Collider, CollisionLayer, HitBox, LayeredAnimation, Player,
+        PreviousTranslation, SheetSprite, Ship, ShipAnimationState, Velocity, Weapon,
     },
+    config::GameConfig,
     constant::{
-        ANIMATION_INTERVAL, SHIP_INITIAL_MOVE_SPEED, SHIP_LASER_COOLDOWN_DURATION,
-        SHIP_LASER_INITIAL_VELOCITY, SHIP_LASER_SPRITE_HEIGHT, SHIP_LASER_SPRITE_WIDTH,
-        SHIP_LASER_TIME_TO_LIVE_DURATION, SHIP_SPRITE_HEIGHT, SHIP_SPRITE_WIDTH,
-        SHIP_STATE_TRANSITION_DURATION,
+        ANIMATION_CLIP_SHIP_LASER, LAYERED_ANIMATION_SHIP, MAX_PLAYERS, SHIP_SPAWN_TRANSLATIONS,
+        SHIP_TINTS, SHIP_Z,
     },
     entity::WeaponComponents,
-    resource::TextureAtlasHandles,
+    resource::{GameSettings, PlayerState, Players},
 };
 use bevy::prelude::*;
+use std::time::Duration;
 
 #[derive(Bundle)]
 pub struct ShipComponents {
     pub ship: Ship,
-    pub constrained_to_arena: ConstrainedToArena,
+    pub player: Player,
+    pub arena_bounds: ArenaBounds,
     pub velocity: Velocity,
     pub hit_box: HitBox,
-    pub animation: Animation,
+    pub collider: Collider,
+    pub collision_layer: CollisionLayer,
+    pub layered_animation: LayeredAnimation,
+    pub previous_translation: PreviousTranslation,
+    pub sheet_sprite: SheetSprite,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
 }
 
-/// Add a new entity to the world with all the needed components to represent a ship
-pub fn initialize_ship(mut commands: Commands, texture_atlas_handles: Res<TextureAtlasHandles>) {
+/// Add the ships of the players that are in the run from the start
+pub fn initialize_ships(
+    mut commands: Commands,
+    layered_animations: Res<LayeredAnimations>,
+    config: Res<GameConfig>,
+    settings: Res<GameSettings>,
+    mut players: ResMut<Players>,
+) {
+    for player_idx in 0..settings.players.min(MAX_PLAYERS) {
+        players.0[player_idx] = Some(PlayerState::new(config.ship.lives, config.ship.bombs));
+        spawn_ship(
+            &mut commands,
+            &layered_animations,
+            &config,
+            Player(player_idx),
+        );
+    }
+}
+
+/// Add a new entity to the world with all the needed components to represent the ship of a player
+pub fn spawn_ship(
+    commands: &mut Commands,
+    layered_animations: &LayeredAnimations,
+    config: &GameConfig,
+    player: Player,
+) {
+    let (ship_x, ship_y) = SHIP_SPAWN_TRANSLATIONS[player.0];
+    let ship_translation = Vec3::new(ship_x, ship_y, SHIP_Z);
+    let sheet_sprite = SheetSprite {
+        color: SHIP_TINTS[player.0],
+        ..Default::default()
+    };
     commands
-        .spawn(SpriteSheetComponents {
-            texture_atlas: texture_atlas_handles.ship.clone(),
-            sprite: TextureAtlasSprite::new(2),
-            ..Default::default()
-        })
-        .with_bundle(ShipComponents {
+        .spawn(ShipComponents {
             ship: Ship {
                 animation_state: ShipAnimationState::Stabilized,
-                move_speed: SHIP_INITIAL_MOVE_SPEED,
-                transition_timer: Timer::new(SHIP_STATE_TRANSITION_DURATION, false),
+                move_speed: config.ship.move_speed,
+                transition_timer: Timer::new(
+                    Duration::from_secs_f32(config.ship.state_transition_duration),
+                    false,
+                ),
             },
-            constrained_to_arena: ConstrainedToArena,
-            hit_box: HitBox(Vec2::new(SHIP_SPRITE_WIDTH, SHIP_SPRITE_HEIGHT)),
+            player,
+            arena_bounds: ArenaBounds::new(BoundsPolicy::Clamp, 0.),
+            hit_box: HitBox(Vec2::new(config.ship.size.0, config.ship.size.1)),
+            // Only the core of the ship can be hit, so lasers can graze its wings
+            collider: Collider::circle(config.ship.core_radius),
+            collision_layer: CollisionLayer::ship(),
             velocity: Velocity(Vec2::default()),
-            animation: Animation {
-                idx_delta: 5,
-                sprite_count: 10,
-                timer: Timer::new(ANIMATION_INTERVAL, true),
-            },
+            layered_animation: layered_animations
+                .get(LAYERED_ANIMATION_SHIP)
+                .expect("Could not get ship animation")
+                .instantiate(),
+            previous_translation: PreviousTranslation(ship_translation),
+            sheet_sprite,
+            transform: Transform::from_translation(ship_translation),
+            global_transform: Default::default(),
         })
         .with_children(|parent| {
-            let mut weapon_cooldown_timer = Timer::new(SHIP_LASER_COOLDOWN_DURATION, false);
-            weapon_cooldown_timer.tick(SHIP_LASER_COOLDOWN_DURATION.as_secs_f32());
+            let laser_config = &config.ship.laser;
+            let mut weapon_cooldown_timer = Timer::new(laser_config.cooldown_duration(), false);
+            weapon_cooldown_timer.tick(laser_config.cooldown);
 
             parent.spawn(WeaponComponents {
                 weapon: Weapon {
                     cooldown_timer: weapon_cooldown_timer,
-                    laser_velocity: Velocity(Vec2::new(
-                        SHIP_LASER_INITIAL_VELOCITY.0,
-                        SHIP_LASER_INITIAL_VELOCITY.1,
-                    )),
-                    laser_hit_box: HitBox(Vec2::new(
-                        SHIP_LASER_SPRITE_WIDTH,
-                        SHIP_LASER_SPRITE_HEIGHT,
-                    )),
-                    laser_time_to_live_duration: SHIP_LASER_TIME_TO_LIVE_DURATION,
-                    laser_initial_sprite_idx: 1,
+                    laser_velocity: laser_config.velocity(),
+                    laser_hit_box: laser_config.hit_box(),
+                    laser_time_to_live_duration: laser_config.time_to_live_duration(),
+                    laser_animation_clip: ANIMATION_CLIP_SHIP_LASER.to_string(),
+                    laser_collision_layer: CollisionLayer::ship_laser(),
+                    laser_bounds: laser_config.arena_bounds(),
                 },
                 transform: Transform {
-                    translation: SHIP_SPRITE_HEIGHT * Vec3::unit_y(),
+                    translation: config.ship.size.1 * Vec3::unit_y(),
                     ..Default::default()
                 },
                 global_transform: Default::default(),
@@ -1,26 +1,42 @@
-use crate::components::{EnemyVariant, HitBox, Velocity};
-use bevy::prelude::*;
-use std::time::Duration;
+use crate::components::{
+    ArenaBounds, CollisionLayer, EnemyVariant, ExplosionKind, HitBox, Player, Velocity,
+};
+use bevy::{input::gamepad::Gamepad, prelude::*};
+use std::{fmt, path::PathBuf, time::Duration};
 
+#[derive(Clone)]
 pub struct SpawnLaserEvent {
     pub laser_source: Entity,
     pub laser_translation: Vec3,
     pub laser_velocity: Velocity,
     pub laser_hit_box: HitBox,
     pub laser_time_to_live_duration: Duration,
-    pub laser_initial_sprite_idx: u32,
+    pub laser_animation_clip: String,
+    pub laser_collision_layer: CollisionLayer,
+    pub laser_bounds: ArenaBounds,
 }
 
+#[derive(Clone)]
 pub struct SpawnEnemyEvent {
     pub enemy_variant: EnemyVariant,
     pub enemy_translation: Vec3,
 }
 
+#[derive(Clone)]
 pub struct SpawnExplosionEvent {
+    pub explosion_kind: ExplosionKind,
     pub explosion_translation: Vec3,
-    pub explosion_time_to_live_duration: Duration,
 }
 
+/// Sent when an entity has shown the last frame of an animation clip that does not repeat
+pub struct AnimationFinishedEvent {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+/// Sent when the game config has been replaced by a newer version of its file
+pub struct ConfigReloadedEvent;
+
 pub struct CollisionLaserEnemyEvent {
     pub laser_entity: Entity,
     pub enemy_entity: Entity,
@@ -30,3 +46,67 @@ pub struct CollisionLaserShipEvent {
     pub laser_entity: Entity,
     pub ship_entity: Entity,
 }
+
+/// Sent when something happens that the players are told about, the game leaves it to the app to
+/// show them
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportEvent {
+    PlayerJoined(Player),
+    PlayerOut {
+        player: Player,
+        score: u32,
+    },
+    GamepadConnected {
+        gamepad: Gamepad,
+        player: Player,
+    },
+    GamepadDisconnected(Gamepad),
+    Paused,
+    Resumed,
+    ConfigReloaded(PathBuf),
+    /// How often a pool had an entity to reuse, sent when the game exits
+    PoolMetrics {
+        pool: &'static str,
+        hits: u64,
+        misses: u64,
+        free: usize,
+    },
+}
+
+impl fmt::Display for ReportEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportEvent::PlayerJoined(player) => write!(f, "Player {} joined", player.0 + 1),
+            ReportEvent::PlayerOut { player, score } => write!(
+                f,
+                "Player {} is out with a score of {}",
+                player.0 + 1,
+                score
+            ),
+            ReportEvent::GamepadConnected { gamepad, player } => write!(
+                f,
+                "Gamepad {} connected for player {}",
+                gamepad.0,
+                player.0 + 1
+            ),
+            ReportEvent::GamepadDisconnected(gamepad) => {
+                write!(f, "Gamepad {} disconnected", gamepad.0)
+            }
+            ReportEvent::Paused => write!(f, "Paused"),
+            ReportEvent::Resumed => write!(f, "Resumed"),
+            ReportEvent::ConfigReloaded(path) => {
+                write!(f, "Reloaded config from {}", path.display())
+            }
+            ReportEvent::PoolMetrics {
+                pool,
+                hits,
+                misses,
+                free,
+            } => write!(
+                f,
+                "{} pool: {} hits, {} misses, {} free",
+                pool, hits, misses, free
+            ),
+        }
+    }
+}
@@ -1,5 +1,28 @@
-use crate::{entity, events, resource, systems};
-use bevy::prelude::*;
+use crate::{
+    animation, atlas,
+    config::GameConfig,
+    constant::{
+        ANIMATION_CLIPS_PLAYED, ANIMATION_MANIFEST_PATH, ATLAS_MANIFEST_PATH, MAX_PLAYERS,
+        STAGE_INTERPOLATE,
+    },
+    entity, events,
+    input::InputBindings,
+    net::NetSession,
+    replay::Replay,
+    resource, systems,
+};
+use bevy::{
+    ecs::Resource,
+    input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent},
+        keyboard::KeyCode,
+        mouse::MouseButton,
+        Axis, Input,
+    },
+    prelude::*,
+    transform::hierarchy::parent_update_system,
+};
+use std::path::PathBuf;
 
 /// A plugin that add and initialize all the entities and systems for running the game
 #[derive(Default)]
@@ -8,30 +31,297 @@ pub struct Game;
 impl Plugin for Game {
     // this is where we set up our plugin
     fn build(&self, app: &mut AppBuilder) {
-        app.init_resource::<resource::TextureAtlasHandles>()
-            .init_resource::<resource::EventReaders>()
-            .add_event::<events::SpawnEnemyEvent>()
-            .add_event::<events::SpawnLaserEvent>()
-            .add_event::<events::SpawnExplosionEvent>()
-            .add_event::<events::CollisionLaserEnemyEvent>()
-            .add_event::<events::CollisionLaserShipEvent>()
-            .add_startup_system(entity::initialize_camera.system())
-            .add_startup_system(entity::initialize_ship.system())
-            .add_startup_system(entity::initialize_enemy_spawner.system())
+        app.add_plugin(Simulation).add_plugin(Presentation);
+    }
+}
+
+/// A plugin that runs the game world. It does not need a window or a GPU, so it can run on top of
+/// `MinimalPlugins`. The atlas and animation manifests have to be loaded as `SpriteAtlases` and
+/// `LayeredAnimations` resources before the plugin is added.
+#[derive(Default)]
+pub struct Simulation;
+
+impl Plugin for Simulation {
+    fn build(&self, app: &mut AppBuilder) {
+        // The config, the settings, the input source, the bindings and the rng are usually given
+        // by the command line before the plugin is added
+        add_default_resource::<GameConfig>(app);
+        add_default_resource::<resource::GameSettings>(app);
+        add_default_resource::<resource::InputSource>(app);
+        add_default_resource::<InputBindings>(app);
+        add_default_resource::<resource::FixedTimestep>(app);
+        add_default_resource::<resource::GameRng>(app);
+        // The keyboard, the gamepads and the mouse are never used when the input plugin is not
+        // there
+        add_default_resource::<Input<KeyCode>>(app);
+        add_default_resource::<Input<GamepadButton>>(app);
+        add_default_resource::<Axis<GamepadAxis>>(app);
+        add_default_resource::<Input<MouseButton>>(app);
+        if app.resources().get::<Events<GamepadEvent>>().is_none() {
+            app.add_event::<GamepadEvent>();
+        }
+
+        // Gameplay events are updated once per tick instead of once per frame, otherwise a frame
+        // that runs no tick could drop events before they are read
+        let mut fixed_schedule = systems::simulation::FixedSchedule::default();
+        fixed_schedule
+            // Entities that a rollback adds back get their children back before anything uses them
+            .add_system(parent_update_system.system())
+            .add_system(systems::feedback::advance_game_time.system())
+            .add_system(systems::simulation::record_previous_translation.system())
+            .add_system(systems::input::follow_pointer.system())
+            .add_system(systems::input::read_player_inputs.system())
+            .add_system(systems::player::join_players.system())
+            .add_system(systems::player::respawn_ships.system())
+            .add_system(Events::<events::SpawnEnemyEvent>::update_system.system())
+            .add_system(Events::<events::SpawnLaserEvent>::update_system.system())
+            .add_system(Events::<events::SpawnExplosionEvent>::update_system.system())
+            .add_system(Events::<events::AnimationFinishedEvent>::update_system.system())
+            .add_system(Events::<events::CollisionLaserEnemyEvent>::update_system.system())
+            .add_system(Events::<events::CollisionLaserShipEvent>::update_system.system())
             .add_system(entity::spawn_enemy.system())
             .add_system(entity::spawn_explosion.system())
             .add_system(entity::spawn_laser.system())
             .add_system(systems::spawner::spawn_enemy_trigger.system())
-            .add_system(systems::ship::keyboard_control.system())
+            .add_system(systems::ship::player_control.system())
             .add_system(systems::ship::animation_state_transition.system())
             .add_system(systems::weapon::fire_laser.system())
+            .add_system(systems::weapon::drop_bomb.system())
             .add_system(systems::motion::apply_velocity.system())
-            .add_system(systems::motion::constrained_to_arena.system())
-            .add_system(systems::collide::check_laser.system())
+            .add_system(systems::motion::apply_arena_bounds.system())
+            .add_system(systems::collide::check_collisions.system())
             .add_system(systems::collide::handle_laser_ship.system())
             .add_system(systems::collide::handle_laser_enemy.system())
-            .add_system(systems::cleanup::despawn_out_of_arena_enemy.system())
+            .add_system(systems::cleanup::despawn_out_of_arena.system())
             .add_system(systems::cleanup::despawn_expired_time_to_live.system())
-            .add_system(systems::animation::texture_atlas_cycle.system());
+            .add_system(systems::animation::play_animation.system())
+            .add_system(systems::animation::play_layered_animation.system())
+            .add_system(systems::cleanup::despawn_finished_explosion.system())
+            .add_system(systems::feedback::explosion_impact.system());
+
+        let networked = {
+            let input_source = app
+                .resources()
+                .get::<resource::InputSource>()
+                .expect("Could not get input source");
+            // A replay is played back with the settings it was recorded with, whatever was given
+            // on the command line
+            if let resource::InputSource::Playback { replay, .. } = &*input_source {
+                let mut settings = app
+                    .resources()
+                    .get_mut::<resource::GameSettings>()
+                    .expect("Could not get game settings");
+                settings.players = replay.players;
+                settings.god_mode = replay.god_mode;
+            }
+            // A game that is played over the network runs its ticks with rollbacks
+            matches!(*input_source, resource::InputSource::Network(_))
+        };
+        let run_ticks = if networked {
+            systems::simulation::run_rollback_timestep(fixed_schedule).thread_local_system()
+        } else {
+            systems::simulation::run_fixed_timestep(fixed_schedule).thread_local_system()
+        };
+
+        app.init_resource::<resource::EventReaders>()
+            .init_resource::<resource::Arena>()
+            .init_resource::<resource::CollisionGrids>()
+            .init_resource::<resource::EntityPools>()
+            .init_resource::<resource::PlayerInputs>()
+            .init_resource::<resource::Players>()
+            .init_resource::<resource::ConnectedGamepads>()
+            .init_resource::<resource::Pointer>()
+            .init_resource::<resource::GameTime>()
+            .init_resource::<resource::HitStop>()
+            .init_resource::<resource::AnimationClips>()
+            .init_resource::<Events<events::SpawnEnemyEvent>>()
+            .init_resource::<Events<events::SpawnLaserEvent>>()
+            .init_resource::<Events<events::SpawnExplosionEvent>>()
+            .init_resource::<Events<events::AnimationFinishedEvent>>()
+            .init_resource::<Events<events::CollisionLaserEnemyEvent>>()
+            .init_resource::<Events<events::CollisionLaserShipEvent>>()
+            .add_event::<events::ConfigReloadedEvent>()
+            .add_event::<events::ReportEvent>()
+            .add_startup_system(entity::initialize_ships.system())
+            .add_startup_system(entity::initialize_enemy_spawner.system())
+            .add_system(run_ticks)
+            .add_system_to_stage(stage::PRE_UPDATE, systems::input::connect_gamepads.system())
+            .add_system_to_stage(stage::LAST, systems::input::save_replay_on_exit.system());
+
+        // Designers can tune the game while it runs in debug builds
+        #[cfg(debug_assertions)]
+        {
+            let config_path = app
+                .resources()
+                .get::<resource::GameSettings>()
+                .expect("Could not get game settings")
+                .config_path
+                .clone();
+            app.add_resource(resource::ConfigWatcher::new(config_path))
+                .add_system_to_stage(
+                    stage::PRE_UPDATE,
+                    systems::config::hot_reload_config.system(),
+                )
+                .add_system(systems::config::apply_reloaded_config.system());
+        }
+    }
+}
+
+/// A plugin that draws the game world, it needs the `Simulation` plugin and `DefaultPlugins`
+#[derive(Default)]
+pub struct Presentation;
+
+impl Plugin for Presentation {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<resource::TextureAtlasHandles>()
+            .init_resource::<resource::ArenaView>()
+            .init_resource::<resource::AccessibilitySettings>()
+            .init_resource::<resource::Rebinding>()
+            .add_stage_after(stage::POST_UPDATE, STAGE_INTERPOLATE)
+            .add_startup_system(entity::initialize_camera.system())
+            .add_startup_system(entity::initialize_background.system())
+            .add_startup_system(entity::initialize_letterbox.system())
+            .add_startup_system(entity::initialize_rebinding_screen.system())
+            .add_system(systems::feedback::camera_shake.system())
+            .add_system(systems::background::scroll_background.system())
+            .add_system(systems::input::toggle_pause.system())
+            .add_system(systems::window::toggle_fullscreen.system())
+            .add_system_to_stage(
+                stage::PRE_UPDATE,
+                systems::window::fit_arena_to_window.system(),
+            )
+            .add_system(systems::input::rebind_actions.system())
+            .add_system(systems::input::show_rebinding_screen.system())
+            .add_system_to_stage(stage::PRE_UPDATE, systems::input::track_pointer.system())
+            .add_system_to_stage(stage::POST_UPDATE, systems::sprite::attach_sprite.system())
+            .add_system_to_stage(
+                STAGE_INTERPOLATE,
+                systems::sprite::sync_sprite_index.system(),
+            )
+            .add_system_to_stage(STAGE_INTERPOLATE, systems::sprite::hide_pooled.system())
+            .add_system_to_stage(
+                STAGE_INTERPOLATE,
+                systems::simulation::interpolate_translation.system(),
+            );
+
+        // Players can try the accessibility options while they play in debug builds
+        #[cfg(debug_assertions)]
+        app.add_system(systems::config::apply_reloaded_accessibility.system());
+    }
+}
+
+/// A plugin that reports what happens to the players and the pools as `ReportEvent`s, it needs the
+/// `Simulation` plugin. It looks at the game world after the frame's ticks, so the ticks that a
+/// rollback runs again are not reported twice.
+#[derive(Default)]
+pub struct Reports;
+
+impl Plugin for Reports {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<resource::AnnouncedPlayers>()
+            .add_system_to_stage(stage::LAST, systems::player::announce_players.system())
+            .add_system_to_stage(stage::LAST, systems::cleanup::report_pool_metrics.system());
+    }
+}
+
+/// The game world without a window, every step runs exactly one tick. The world and the
+/// resources of the app can be inspected and changed in between steps.
+pub struct HeadlessGame {
+    pub app: App,
+}
+
+impl HeadlessGame {
+    pub fn new(config: GameConfig, seed: u64) -> Self {
+        Self::build(config, seed, |_| {})
+    }
+
+    /// A game that is played over the network, both players are in it from the start. A step runs
+    /// no tick when the game is waiting for the other player's input.
+    pub fn networked(config: GameConfig, session: NetSession) -> Self {
+        let seed = session.seed();
+        Self::build(config, seed, |app_builder| {
+            app_builder
+                .add_resource(resource::InputSource::Network(session))
+                .add_resource(resource::GameSettings {
+                    players: MAX_PLAYERS,
+                    ..Default::default()
+                });
+        })
+    }
+
+    /// A game whose input is read from the devices and recorded into the replay, it is played
+    /// with the replay's seed, players and god mode
+    pub fn recording(config: GameConfig, replay: Replay, path: PathBuf) -> Self {
+        let seed = replay.seed;
+        Self::build(config, seed, |app_builder| {
+            app_builder
+                .add_resource(resource::GameSettings {
+                    players: replay.players,
+                    god_mode: replay.god_mode,
+                    ..Default::default()
+                })
+                .add_resource(resource::InputSource::Recording { path, replay });
+        })
+    }
+
+    /// A game that plays the replay back, the game exits once the replay runs out of input
+    pub fn playback(config: GameConfig, replay: Replay) -> Self {
+        let seed = replay.seed;
+        Self::build(config, seed, |app_builder| {
+            app_builder.add_resource(resource::InputSource::Playback { replay, tick: 0 });
+        })
+    }
+
+    fn build(config: GameConfig, seed: u64, setup: impl FnOnce(&mut AppBuilder)) -> Self {
+        let sprite_atlases = atlas::SpriteAtlases::load(atlas::asset_path(ATLAS_MANIFEST_PATH))
+            .expect("Could not load atlas manifest");
+        sprite_atlases
+            .require_clips(&ANIMATION_CLIPS_PLAYED)
+            .expect("Could not find the clips of the game");
+        let layered_animations = animation::LayeredAnimations::load(
+            atlas::asset_path(ANIMATION_MANIFEST_PATH),
+            sprite_atlases.clips(),
+        )
+        .expect("Could not load animation manifest");
+
+        let mut app_builder = App::build();
+        app_builder
+            .add_resource(config)
+            .add_resource(sprite_atlases)
+            .add_resource(layered_animations)
+            .add_resource(resource::GameRng::new(seed))
+            .add_resource(resource::FixedTimestep {
+                real_time: false,
+                ..Default::default()
+            });
+        setup(&mut app_builder);
+        app_builder
+            .add_plugins(MinimalPlugins)
+            .add_plugin(Simulation);
+
+        // The app is stepped by hand instead of by its runner, so the startup systems are run here
+        let mut app = std::mem::take(&mut app_builder.app);
+        app.startup_schedule
+            .initialize(&mut app.world, &mut app.resources);
+        app.startup_executor.run(
+            &mut app.startup_schedule,
+            &mut app.world,
+            &mut app.resources,
+        );
+        Self { app }
+    }
+
+    /// Run the given number of ticks
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+}
+
+/// Add a resource with its default value, unless it was given before the plugin was added
+fn add_default_resource<T: Resource + Default>(app: &mut AppBuilder) {
+    if app.resources().get::<T>().is_none() {
+        app.add_resource(T::default());
     }
 }
@@ -0,0 +1,380 @@
+use crate::{components::Player, constant::MAX_PLAYERS};
+use bevy::{
+    input::{
+        gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType},
+        keyboard::KeyCode,
+        Axis, Input,
+    },
+    math::Vec2,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Something the player can do, every action can be bound to any number of keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Fire,
+    Bomb,
+    /// Move slower for precise dodging
+    Focus,
+    Pause,
+}
+
+impl Action {
+    /// All the actions, in the order they are shown when rebinding
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Fire,
+        Action::Bomb,
+        Action::Focus,
+        Action::Pause,
+    ];
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::MoveLeft => "move left",
+            Action::MoveRight => "move right",
+            Action::MoveUp => "move up",
+            Action::MoveDown => "move down",
+            Action::Fire => "fire",
+            Action::Bomb => "bomb",
+            Action::Focus => "focus",
+            Action::Pause => "pause",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        /// Keys that can be bound to an action, with the names they have in the settings file
+        const KEY_NAMES: &[(&str, KeyCode)] = &[$((stringify!($key), KeyCode::$key)),*];
+    };
+}
+
+key_names!(
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Key1, Key2, Key3,
+    Key4, Key5, Key6, Key7, Key8, Key9, Key0, Left, Right, Up, Down, Space, Escape, Back, Tab,
+    LShift, RShift, LControl, RControl, LAlt, RAlt, Comma, Period, Slash, Semicolon, Apostrophe,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+);
+
+/// Get a key from its name in the settings file
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| *key_name == name)
+        .map(|(_, key)| *key)
+}
+
+/// Get the name of a key in the settings file, keys without a name can not be bound
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, named_key)| *named_key == key)
+        .map(|(name, _)| *name)
+}
+
+/// The gamepad buttons of every action, they are the same on every gamepad and can not be rebound
+const GAMEPAD_BUTTONS: &[(Action, GamepadButtonType)] = &[
+    (Action::MoveLeft, GamepadButtonType::DPadLeft),
+    (Action::MoveRight, GamepadButtonType::DPadRight),
+    (Action::MoveUp, GamepadButtonType::DPadUp),
+    (Action::MoveDown, GamepadButtonType::DPadDown),
+    (Action::Fire, GamepadButtonType::South),
+    (Action::Bomb, GamepadButtonType::East),
+    (Action::Focus, GamepadButtonType::LeftTrigger),
+    (Action::Focus, GamepadButtonType::RightTrigger),
+    (Action::Pause, GamepadButtonType::Start),
+];
+
+fn gamepad_buttons(gamepad: Gamepad, action: Action) -> impl Iterator<Item = GamepadButton> {
+    GAMEPAD_BUTTONS
+        .iter()
+        .filter(move |(button_action, _)| *button_action == action)
+        .map(move |(_, button_type)| GamepadButton(gamepad, *button_type))
+}
+
+/// Check if any of the gamepad's buttons for the action is held down
+pub fn gamepad_pressed(
+    gamepad_input: &Input<GamepadButton>,
+    gamepad: Gamepad,
+    action: Action,
+) -> bool {
+    gamepad_buttons(gamepad, action).any(|button| gamepad_input.pressed(button))
+}
+
+/// Check if any of the gamepad's buttons for the action has been pressed during this frame
+pub fn gamepad_just_pressed(
+    gamepad_input: &Input<GamepadButton>,
+    gamepad: Gamepad,
+    action: Action,
+) -> bool {
+    gamepad_buttons(gamepad, action).any(|button| gamepad_input.just_pressed(button))
+}
+
+/// Tilt of the gamepad's left stick. Tilts inside the deadzone are ignored, and the rest of the
+/// range is stretched so that the tilt still grows from zero at the edge of the deadzone.
+pub fn gamepad_stick(gamepad_axes: &Axis<GamepadAxis>, gamepad: Gamepad, deadzone: f32) -> Vec2 {
+    let axis = |axis_type| {
+        gamepad_axes
+            .get(GamepadAxis(gamepad, axis_type))
+            .unwrap_or_default()
+    };
+    let stick = Vec2::new(
+        axis(GamepadAxisType::LeftStickX),
+        axis(GamepadAxisType::LeftStickY),
+    );
+
+    let tilt = stick.length();
+    if tilt <= deadzone {
+        return Vec2::zero();
+    }
+    stick / tilt * ((tilt - deadzone) / (1. - deadzone)).min(1.)
+}
+
+/// How the bindings are written in the settings file. Actions that are left out keep their
+/// default keys.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BindingsFile {
+    bindings: BTreeMap<Action, Vec<String>>,
+    #[serde(default)]
+    second_player_bindings: BTreeMap<Action, Vec<String>>,
+}
+
+/// Errors that make a settings file unusable
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(PathBuf, io::Error),
+    Parse(ron::Error),
+    UnknownKey {
+        player: Player,
+        action: Action,
+        key: String,
+    },
+    Conflict {
+        key: &'static str,
+        first: (Player, Action),
+        second: (Player, Action),
+    },
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io(path, err) => {
+                write!(f, "could not access {}: {}", path.display(), err)
+            }
+            BindingsError::Parse(err) => write!(f, "invalid settings: {}", err),
+            BindingsError::UnknownKey {
+                player,
+                action,
+                key,
+            } => write!(
+                f,
+                "\"{}\" bound to {} of player {} is not a key",
+                key,
+                action,
+                player.0 + 1
+            ),
+            BindingsError::Conflict {
+                key,
+                first: (first_player, first_action),
+                second: (second_player, second_action),
+            } => write!(
+                f,
+                "\"{}\" is bound to both {} of player {} and {} of player {}",
+                key,
+                first_action,
+                first_player.0 + 1,
+                second_action,
+                second_player.0 + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+/// The keys that are bound to each action of every player
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputBindings {
+    players: [BTreeMap<Action, Vec<KeyCode>>; MAX_PLAYERS],
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let mut first_player = BTreeMap::new();
+        first_player.insert(Action::MoveLeft, vec![KeyCode::A, KeyCode::Left]);
+        first_player.insert(Action::MoveRight, vec![KeyCode::D, KeyCode::Right]);
+        first_player.insert(Action::MoveUp, vec![KeyCode::W, KeyCode::Up]);
+        first_player.insert(Action::MoveDown, vec![KeyCode::S, KeyCode::Down]);
+        first_player.insert(Action::Fire, vec![KeyCode::Space]);
+        first_player.insert(Action::Bomb, vec![KeyCode::X]);
+        first_player.insert(Action::Focus, vec![KeyCode::LShift]);
+        first_player.insert(Action::Pause, vec![KeyCode::P, KeyCode::Escape]);
+
+        // The second player sits on the right side of the keyboard
+        let mut second_player = BTreeMap::new();
+        second_player.insert(Action::MoveLeft, vec![KeyCode::J]);
+        second_player.insert(Action::MoveRight, vec![KeyCode::L]);
+        second_player.insert(Action::MoveUp, vec![KeyCode::I]);
+        second_player.insert(Action::MoveDown, vec![KeyCode::K]);
+        second_player.insert(Action::Fire, vec![KeyCode::U]);
+        second_player.insert(Action::Bomb, vec![KeyCode::O]);
+        second_player.insert(Action::Focus, vec![KeyCode::Semicolon]);
+        second_player.insert(Action::Pause, Vec::new());
+
+        Self {
+            players: [first_player, second_player],
+        }
+    }
+}
+
+impl InputBindings {
+    /// Read and validate a settings file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BindingsError> {
+        let path = path.as_ref();
+        let content =
+            fs::read_to_string(path).map_err(|err| BindingsError::Io(path.to_path_buf(), err))?;
+        Self::parse(&content)
+    }
+
+    /// Parse and validate the content of a settings file
+    pub fn parse(content: &str) -> Result<Self, BindingsError> {
+        let file: BindingsFile = ron::de::from_str(content).map_err(BindingsError::Parse)?;
+
+        let mut input_bindings = Self::default();
+        let players = [file.bindings, file.second_player_bindings];
+        for (player_idx, bindings) in players.iter().enumerate() {
+            let player = Player(player_idx);
+            for (action, key_names) in bindings.iter() {
+                let keys = key_names
+                    .iter()
+                    .map(|name| {
+                        key_from_name(name).ok_or_else(|| BindingsError::UnknownKey {
+                            player,
+                            action: *action,
+                            key: name.clone(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                input_bindings.set_keys(player, *action, keys);
+            }
+        }
+        input_bindings.check_conflicts()?;
+        Ok(input_bindings)
+    }
+
+    /// Write the bindings to a settings file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), BindingsError> {
+        let path = path.as_ref();
+        let key_names = |bindings: &BTreeMap<Action, Vec<KeyCode>>| {
+            bindings
+                .iter()
+                .map(|(action, keys)| {
+                    let key_names = keys
+                        .iter()
+                        .filter_map(|key| key_name(*key))
+                        .map(str::to_string)
+                        .collect();
+                    (*action, key_names)
+                })
+                .collect()
+        };
+        let [first_player, second_player] = &self.players;
+        let file = BindingsFile {
+            bindings: key_names(first_player),
+            second_player_bindings: key_names(second_player),
+        };
+        let content = ron::ser::to_string_pretty(&file, PrettyConfig::new().with_depth_limit(2))
+            .expect("Could not serialize input bindings");
+        fs::write(path, content).map_err(|err| BindingsError::Io(path.to_path_buf(), err))
+    }
+
+    pub fn keys(&self, player: Player, action: Action) -> &[KeyCode] {
+        self.players
+            .get(player.0)
+            .and_then(|bindings| bindings.get(&action))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn set_keys(&mut self, player: Player, action: Action, keys: Vec<KeyCode>) {
+        self.players[player.0].insert(action, keys);
+    }
+
+    /// Get the player and the action that a key is bound to
+    pub fn action(&self, key: KeyCode) -> Option<(Player, Action)> {
+        self.players
+            .iter()
+            .enumerate()
+            .flat_map(|(player_idx, bindings)| {
+                bindings
+                    .iter()
+                    .map(move |(action, keys)| (Player(player_idx), *action, keys))
+            })
+            .find(|(_, _, keys)| keys.contains(&key))
+            .map(|(player, action, _)| (player, action))
+    }
+
+    /// Check if any of the keys bound to the player's action is held down
+    pub fn pressed(&self, keyboard_input: &Input<KeyCode>, player: Player, action: Action) -> bool {
+        self.keys(player, action)
+            .iter()
+            .any(|key| keyboard_input.pressed(*key))
+    }
+
+    /// Check if any of the keys bound to the player's action has been pressed during this frame
+    pub fn just_pressed(
+        &self,
+        keyboard_input: &Input<KeyCode>,
+        player: Player,
+        action: Action,
+    ) -> bool {
+        self.keys(player, action)
+            .iter()
+            .any(|key| keyboard_input.just_pressed(*key))
+    }
+
+    /// Make sure that no key is bound to more than one action
+    pub fn check_conflicts(&self) -> Result<(), BindingsError> {
+        let mut bound: BTreeMap<&'static str, (Player, Action)> = BTreeMap::new();
+        for (player_idx, bindings) in self.players.iter().enumerate() {
+            let player = Player(player_idx);
+            for (action, keys) in bindings.iter() {
+                for key in keys.iter() {
+                    let name = key_name(*key).unwrap_or("?");
+                    match bound.get(name) {
+                        Some(first) if *first != (player, *action) => {
+                            return Err(BindingsError::Conflict {
+                                key: name,
+                                first: *first,
+                                second: (player, *action),
+                            })
+                        }
+                        _ => {
+                            bound.insert(name, (player, *action));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
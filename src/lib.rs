@@ -0,0 +1,16 @@
+pub mod animation;
+pub mod atlas;
+pub mod cli;
+pub mod collision;
+pub mod components;
+pub mod config;
+pub mod constant;
+pub mod entity;
+pub mod events;
+pub mod game;
+pub mod input;
+pub mod net;
+pub mod replay;
+pub mod resource;
+pub mod snapshot;
+pub mod systems;
@@ -1,25 +1,197 @@
-mod components;
-mod constant;
-mod entity;
-mod events;
-mod game;
-mod resource;
-mod systems;
-
-use bevy::prelude::{App, DefaultPlugins, WindowDescriptor};
-use constant::{ARENA_HEIGHT, ARENA_SCALE, ARENA_WIDTH};
+use bevy::prelude::*;
+use bevy_spaceshooter::{
+    animation::LayeredAnimations,
+    atlas::{self, SpriteAtlases},
+    cli::{CliOptions, USAGE},
+    config::{ConfigError, GameConfig},
+    constant::{
+        ANIMATION_CLIPS_PLAYED, ANIMATION_MANIFEST_PATH, ATLAS_MANIFEST_PATH, CONFIG_PATH,
+        MAX_PLAYERS, SETTINGS_PATH,
+    },
+    events::ReportEvent,
+    game,
+    input::{BindingsError, InputBindings},
+    net::{NetConditions, NetSession},
+    replay::Replay,
+    resource::{EventReaders, FixedTimestep, GameRng, GameSettings, InputSource},
+};
+use std::{env, io, process, time::Duration};
 
 fn main() {
-    App::build()
-        .add_resource(WindowDescriptor {
+    let options = match CliOptions::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // The constants are used when there is no config file, unless a config file was asked for
+    let config_path = options
+        .config
+        .clone()
+        .unwrap_or_else(|| atlas::asset_path(CONFIG_PATH));
+    let config = match GameConfig::load(&config_path) {
+        Ok(config) => config,
+        Err(ConfigError::Io(_, err))
+            if options.config.is_none() && err.kind() == io::ErrorKind::NotFound =>
+        {
+            GameConfig::default()
+        }
+        Err(err) => {
+            eprintln!("Could not load config: {}", err);
+            process::exit(1);
+        }
+    };
+
+    // The default bindings are used until the player changes them
+    let bindings_path = atlas::asset_path(SETTINGS_PATH);
+    let input_bindings = match InputBindings::load(&bindings_path) {
+        Ok(input_bindings) => input_bindings,
+        Err(BindingsError::Io(_, err)) if err.kind() == io::ErrorKind::NotFound => {
+            InputBindings::default()
+        }
+        Err(err) => {
+            eprintln!("Could not load input bindings: {}", err);
+            process::exit(1);
+        }
+    };
+
+    let sprite_atlases =
+        SpriteAtlases::load(atlas::asset_path(ATLAS_MANIFEST_PATH)).and_then(|sprite_atlases| {
+            sprite_atlases.require_clips(&ANIMATION_CLIPS_PLAYED)?;
+            Ok(sprite_atlases)
+        });
+    let sprite_atlases = match sprite_atlases {
+        Ok(sprite_atlases) => sprite_atlases,
+        Err(err) => {
+            eprintln!("Could not load atlas manifest: {}", err);
+            process::exit(1);
+        }
+    };
+    let layered_animations = match LayeredAnimations::load(
+        atlas::asset_path(ANIMATION_MANIFEST_PATH),
+        sprite_atlases.clips(),
+    ) {
+        Ok(layered_animations) => layered_animations,
+        Err(err) => {
+            eprintln!("Could not load animation manifest: {}", err);
+            process::exit(1);
+        }
+    };
+
+    // A replay is played back with the seed it was recorded with, the simulation takes the players
+    // and the god mode from it too
+    let replay = options
+        .replay
+        .as_ref()
+        .map(|path| match Replay::load(path) {
+            Ok(replay) => replay,
+            Err(err) => {
+                eprintln!("Could not load replay: {}", err);
+                process::exit(1);
+            }
+        });
+    let mut seed = replay
+        .as_ref()
+        .map(|replay| replay.seed)
+        .or(options.seed)
+        .or(config.seed)
+        .unwrap_or_else(rand::random);
+
+    // A game played over the network starts once the other player is there, with the host's seed
+    let conditions = NetConditions {
+        latency: Duration::from_millis(options.latency),
+        packet_loss: options.packet_loss / 100.,
+    };
+    let net_session = if let Some(port) = options.host {
+        println!("Waiting for the other player on port {}", port);
+        Some(NetSession::host(port, seed, conditions))
+    } else if let Some(address) = &options.connect {
+        println!("Joining the game at {}", address);
+        Some(NetSession::connect(address, conditions))
+    } else {
+        None
+    };
+    let net_session = net_session.map(|net_session| match net_session {
+        Ok(net_session) => net_session,
+        Err(err) => {
+            eprintln!("Could not start the network game: {}", err);
+            process::exit(1);
+        }
+    });
+    if let Some(net_session) = &net_session {
+        seed = net_session.seed();
+    }
+    // Runs can be reproduced by giving this seed back to the game
+    println!("Seed: {}", seed);
+
+    let players = if net_session.is_some() {
+        MAX_PLAYERS
+    } else {
+        options.players
+    };
+    let input_source = match (replay, options.record, net_session) {
+        (_, _, Some(net_session)) => InputSource::Network(net_session),
+        (Some(replay), _, None) => InputSource::Playback { replay, tick: 0 },
+        (None, Some(path), None) => InputSource::Recording {
+            path,
+            replay: Replay::new(seed, players, options.god_mode),
+        },
+        (None, None, None) => InputSource::Devices,
+    };
+
+    let (arena_width, arena_height) = config.arena;
+    let mut app = App::build();
+    app.add_resource(config)
+        .add_resource(GameRng::new(seed))
+        .add_resource(input_source)
+        .add_resource(input_bindings)
+        .add_resource(sprite_atlases)
+        .add_resource(layered_animations)
+        .add_resource(GameSettings {
+            scale: options.scale,
+            players,
+            pointer_control: options.pointer_control,
+            god_mode: options.god_mode,
+            config_path,
+            bindings_path,
+        });
+
+    if options.headless {
+        // Without a window the game runs as fast as it can, until the ships are destroyed or the
+        // replay ends
+        app.add_resource(FixedTimestep {
+            real_time: false,
+            ..Default::default()
+        })
+        .add_plugins(MinimalPlugins)
+        .add_plugin(game::Simulation)
+        .add_plugin(game::Reports);
+    } else {
+        app.add_resource(WindowDescriptor {
             title: "Space shooter!".to_string(),
-            width: (ARENA_WIDTH * ARENA_SCALE) as u32,
-            height: (ARENA_HEIGHT * ARENA_SCALE) as u32,
-            resizable: false,
+            width: (arena_width * options.scale) as u32,
+            height: (arena_height * options.scale) as u32,
+            resizable: true,
             vsync: true,
             ..Default::default()
         })
         .add_plugins(DefaultPlugins)
         .add_plugin(game::Game)
+        .add_plugin(game::Reports);
+    }
+    app.add_system_to_stage(stage::LAST, print_reports.system())
         .run();
 }
+
+/// Tell on the terminal what the game reported during the frame
+fn print_reports(report_events: Res<Events<ReportEvent>>, mut event_readers: ResMut<EventReaders>) {
+    for report in event_readers.reports.iter(&report_events) {
+        println!("{}", report);
+    }
+}
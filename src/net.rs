@@ -0,0 +1,478 @@
+use crate::{
+    components::Player,
+    constant::{
+        NET_DISCONNECT_TIMEOUT, NET_HANDSHAKE_TIMEOUT, NET_JOIN_INTERVAL, NET_MAGIC,
+        NET_MAX_PREDICTION_TICKS, NET_PROTOCOL_VERSION,
+    },
+    resource::{PlayerInput, PlayerInputs},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+/// Errors that stop two games from being played together
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    UnknownAddress(String),
+    NotAPacket,
+    VersionMismatch { found: u16, expected: u16 },
+    Timeout,
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(err) => write!(f, "network error: {}", err),
+            NetError::UnknownAddress(address) => write!(f, "could not resolve {}", address),
+            NetError::NotAPacket => write!(f, "the packet is not from this game"),
+            NetError::VersionMismatch { found, expected } => write!(
+                f,
+                "the other game uses protocol version {} but this game uses version {}",
+                found, expected
+            ),
+            NetError::Timeout => write!(f, "the other player did not answer in time"),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+impl From<io::Error> for NetError {
+    fn from(err: io::Error) -> Self {
+        NetError::Io(err)
+    }
+}
+
+/// Network conditions that are simulated on top of the real ones, so two games on the same
+/// machine can be played as if the players were far apart
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetConditions {
+    /// How long every packet is held back before it is sent
+    pub latency: Duration,
+    /// Chance of a packet being dropped instead of sent, between 0 and 1
+    pub packet_loss: f32,
+}
+
+/// What two games say to each other.
+///
+/// A packet starts with a 4 bytes magic number, the protocol's version as a `u16` and the kind of
+/// packet as a `u8`. A welcome carries the seed as a `u64`. Inputs carry the number of ticks of
+/// the receiver whose input has arrived as a `u32`, the tick of the first input as a `u32`, the
+/// number of inputs as a `u8` and the inputs themselves, as a `u8` for the actions and an `i8` for
+/// each axis of the stick. All the numbers are little endian.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Packet {
+    /// Sent by the player who joins until the host answers
+    Join,
+    /// The host's answer, both games start from the same seed
+    Welcome { seed: u64 },
+    /// The sender's input of consecutive ticks
+    Inputs {
+        received: u32,
+        start_tick: u32,
+        inputs: Vec<PlayerInput>,
+    },
+}
+
+impl Packet {
+    const JOIN: u8 = 0;
+    const WELCOME: u8 = 1;
+    const INPUTS: u8 = 2;
+
+    /// Inputs that fit in a single packet
+    pub const MAX_INPUTS: usize = u8::MAX as usize;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(NET_MAGIC);
+        bytes.extend_from_slice(&NET_PROTOCOL_VERSION.to_le_bytes());
+        match self {
+            Packet::Join => bytes.push(Self::JOIN),
+            Packet::Welcome { seed } => {
+                bytes.push(Self::WELCOME);
+                bytes.extend_from_slice(&seed.to_le_bytes());
+            }
+            Packet::Inputs {
+                received,
+                start_tick,
+                inputs,
+            } => {
+                let inputs = &inputs[..inputs.len().min(Self::MAX_INPUTS)];
+                bytes.push(Self::INPUTS);
+                bytes.extend_from_slice(&received.to_le_bytes());
+                bytes.extend_from_slice(&start_tick.to_le_bytes());
+                bytes.push(inputs.len() as u8);
+                for input in inputs.iter() {
+                    bytes.push(input.bits());
+                    bytes.extend(input.raw_stick().iter().map(|axis| *axis as u8));
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, NetError> {
+        let mut reader = PacketReader { bytes };
+        if reader.take(NET_MAGIC.len())? != &NET_MAGIC[..] {
+            return Err(NetError::NotAPacket);
+        }
+        let version = u16::from_le_bytes(reader.take_array()?);
+        if version != NET_PROTOCOL_VERSION {
+            return Err(NetError::VersionMismatch {
+                found: version,
+                expected: NET_PROTOCOL_VERSION,
+            });
+        }
+
+        let [kind] = reader.take_array::<[u8; 1]>()?;
+        let packet = match kind {
+            Self::JOIN => Packet::Join,
+            Self::WELCOME => Packet::Welcome {
+                seed: u64::from_le_bytes(reader.take_array()?),
+            },
+            Self::INPUTS => {
+                let received = u32::from_le_bytes(reader.take_array()?);
+                let start_tick = u32::from_le_bytes(reader.take_array()?);
+                let [count] = reader.take_array::<[u8; 1]>()?;
+                let mut inputs = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let [bits, stick_x, stick_y] = reader.take_array::<[u8; 3]>()?;
+                    inputs.push(PlayerInput::from_raw(bits, [stick_x as i8, stick_y as i8]));
+                }
+                Packet::Inputs {
+                    received,
+                    start_tick,
+                    inputs,
+                }
+            }
+            _ => return Err(NetError::NotAPacket),
+        };
+        if !reader.bytes.is_empty() {
+            return Err(NetError::NotAPacket);
+        }
+        Ok(packet)
+    }
+}
+
+struct PacketReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], NetError> {
+        if self.bytes.len() < count {
+            return Err(NetError::NotAPacket);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_array<T>(&mut self) -> Result<T, NetError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let mut array = T::default();
+        let count = array.as_mut().len();
+        array.as_mut().copy_from_slice(self.take(count)?);
+        Ok(array)
+    }
+}
+
+/// A game that two players play over UDP, each from their own machine.
+///
+/// Every tick, both games send their player's input to each other. Until the other player's input
+/// of a tick has arrived, the tick is run with a guess, the last input that did arrive. Guesses
+/// are checked as the real input comes in, and the first tick that was guessed wrong is reported
+/// so the game world can be rolled back and the ticks from there run again.
+pub struct NetSession {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    seed: u64,
+    local_player: Player,
+    conditions: NetConditions,
+    /// Packets that are held back by the simulated latency, with the time they can be sent at
+    delayed: VecDeque<(Instant, Vec<u8>)>,
+    /// Decides which packets are dropped by the simulated packet loss, it is not the game's rng so
+    /// the game world does not depend on it
+    loss_rng: StdRng,
+    /// The tick that is being run, or the next one that will be
+    tick: usize,
+    local_inputs: Vec<PlayerInput>,
+    /// Input of the other player, it is only kept in order so there is never a gap
+    remote_inputs: Vec<PlayerInput>,
+    /// Input of the other player that each tick was run with, received or guessed
+    remote_inputs_used: Vec<PlayerInput>,
+    /// Number of ticks whose local input has arrived at the other game
+    local_inputs_received: usize,
+    mispredicted_tick: Option<usize>,
+    last_received: Instant,
+}
+
+impl NetSession {
+    /// Wait for another game to join on the given port. The host is the first player, and the
+    /// game that joins gets the host's seed. Both games have to use the same config.
+    pub fn host(port: u16, seed: u64, conditions: NetConditions) -> Result<Self, NetError> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_read_timeout(Some(NET_JOIN_INTERVAL))?;
+
+        let deadline = Instant::now() + NET_HANDSHAKE_TIMEOUT;
+        let mut buffer = [0; 1024];
+        let peer = loop {
+            if Instant::now() > deadline {
+                return Err(NetError::Timeout);
+            }
+            match socket.recv_from(&mut buffer) {
+                Ok((len, from)) => match Packet::decode(&buffer[..len]) {
+                    Ok(Packet::Join) => break from,
+                    Err(err @ NetError::VersionMismatch { .. }) => return Err(err),
+                    _ => {}
+                },
+                Err(err) if is_timeout(&err) => {}
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        let mut session = Self::new(socket, peer, seed, Player(0), conditions)?;
+        session.send(&Packet::Welcome { seed });
+        Ok(session)
+    }
+
+    /// Join the game that is hosted at the given address, as the second player
+    pub fn connect(address: &str, conditions: NetConditions) -> Result<Self, NetError> {
+        let peer = address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .ok_or_else(|| NetError::UnknownAddress(address.to_string()))?;
+        let socket = if peer.is_ipv4() {
+            UdpSocket::bind("0.0.0.0:0")?
+        } else {
+            UdpSocket::bind("[::]:0")?
+        };
+        socket.set_read_timeout(Some(NET_JOIN_INTERVAL))?;
+
+        // The seed is not known until the host answers
+        let mut session = Self::new(socket, peer, 0, Player(1), conditions)?;
+        session.socket.set_nonblocking(false)?;
+        let deadline = Instant::now() + NET_HANDSHAKE_TIMEOUT;
+        let mut buffer = [0; 1024];
+        'handshake: loop {
+            if Instant::now() > deadline {
+                return Err(NetError::Timeout);
+            }
+            session.send(&Packet::Join);
+            let wait_until = Instant::now() + NET_JOIN_INTERVAL;
+            while Instant::now() < wait_until {
+                session.flush_delayed();
+                match session.socket.recv_from(&mut buffer) {
+                    Ok((len, from)) if from == peer => match Packet::decode(&buffer[..len]) {
+                        Ok(Packet::Welcome { seed }) => {
+                            session.seed = seed;
+                            break 'handshake;
+                        }
+                        Err(err @ NetError::VersionMismatch { .. }) => return Err(err),
+                        _ => {}
+                    },
+                    Ok(_) => {}
+                    Err(err) if is_timeout(&err) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+
+        session.socket.set_nonblocking(true)?;
+        session.last_received = Instant::now();
+        Ok(session)
+    }
+
+    fn new(
+        socket: UdpSocket,
+        peer: SocketAddr,
+        seed: u64,
+        local_player: Player,
+        conditions: NetConditions,
+    ) -> Result<Self, NetError> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            peer,
+            seed,
+            local_player,
+            conditions,
+            delayed: VecDeque::new(),
+            loss_rng: StdRng::from_entropy(),
+            tick: 0,
+            local_inputs: Vec::new(),
+            remote_inputs: Vec::new(),
+            remote_inputs_used: Vec::new(),
+            local_inputs_received: 0,
+            mispredicted_tick: None,
+            last_received: Instant::now(),
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The player who is controlled with this game's devices
+    pub fn local_player(&self) -> Player {
+        self.local_player
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Number of ticks that have been run with the real input of both players
+    pub fn confirmed_ticks(&self) -> usize {
+        self.remote_inputs.len().min(self.tick)
+    }
+
+    /// Check if the next tick can be run. A game that is too far ahead of the other player's input
+    /// waits for it, so rollbacks stay short.
+    pub fn can_advance(&self) -> bool {
+        self.tick < self.remote_inputs.len() + NET_MAX_PREDICTION_TICKS
+    }
+
+    /// Move on to the next tick once a tick has been run
+    pub fn advance(&mut self) {
+        self.tick += 1;
+    }
+
+    /// Go back to an earlier tick, the ticks from there are run again with the same local input
+    pub fn rewind(&mut self, tick: usize) {
+        self.tick = tick.min(self.tick);
+    }
+
+    /// Get the first tick that was run with a wrong guess since the last time this was called
+    pub fn take_mispredicted_tick(&mut self) -> Option<usize> {
+        self.mispredicted_tick.take()
+    }
+
+    /// Check if nothing has been heard from the other game for a while
+    pub fn is_disconnected(&self) -> bool {
+        self.last_received.elapsed() > NET_DISCONNECT_TIMEOUT
+    }
+
+    /// The input of both players for the tick that is being run. The local input is only read the
+    /// first time a tick is run, the other player's input is guessed when it has not arrived yet.
+    pub fn inputs(&mut self, read_local_input: impl FnOnce() -> PlayerInput) -> PlayerInputs {
+        if self.tick == self.local_inputs.len() {
+            self.local_inputs.push(read_local_input());
+        }
+        let local_input = self.local_inputs[self.tick];
+        let remote_input = match self.remote_inputs.get(self.tick) {
+            Some(remote_input) => *remote_input,
+            None => self.remote_inputs.last().copied().unwrap_or_default(),
+        };
+        if self.tick < self.remote_inputs_used.len() {
+            self.remote_inputs_used[self.tick] = remote_input;
+        } else {
+            self.remote_inputs_used.push(remote_input);
+        }
+
+        let mut player_inputs = PlayerInputs::default();
+        player_inputs.0[self.local_player.0] = local_input;
+        player_inputs.0[self.remote_player().0] = remote_input;
+        player_inputs
+    }
+
+    /// Send the packets whose simulated latency is over and read the packets that have arrived
+    pub fn poll(&mut self) {
+        self.flush_delayed();
+
+        let mut buffer = [0; 2048];
+        // Reading stops once nothing is left, errors are treated like lost packets
+        while let Ok((len, from)) = self.socket.recv_from(&mut buffer) {
+            if from != self.peer {
+                continue;
+            }
+            match Packet::decode(&buffer[..len]) {
+                // The answer to the joining game has been lost
+                Ok(Packet::Join) if self.local_player == Player(0) => {
+                    self.send(&Packet::Welcome { seed: self.seed })
+                }
+                Ok(Packet::Inputs {
+                    received,
+                    start_tick,
+                    inputs,
+                }) => {
+                    self.last_received = Instant::now();
+                    self.receive_inputs(received as usize, start_tick as usize, inputs);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Send the local input that has not arrived at the other game yet. It is sent even when there
+    /// is none, so the other game knows which of its inputs have arrived.
+    pub fn send_inputs(&mut self) {
+        let start_tick = self.local_inputs_received.min(self.local_inputs.len());
+        let end_tick = self.local_inputs.len().min(start_tick + Packet::MAX_INPUTS);
+        let packet = Packet::Inputs {
+            received: self.remote_inputs.len() as u32,
+            start_tick: start_tick as u32,
+            inputs: self.local_inputs[start_tick..end_tick].to_vec(),
+        };
+        self.send(&packet);
+    }
+
+    fn remote_player(&self) -> Player {
+        Player(1 - self.local_player.0)
+    }
+
+    fn receive_inputs(&mut self, received: usize, start_tick: usize, inputs: Vec<PlayerInput>) {
+        self.local_inputs_received = self.local_inputs_received.max(received);
+        for (tick, input) in (start_tick..).zip(inputs) {
+            // Inputs that were already received are skipped, the ones after a gap are sent again
+            if tick != self.remote_inputs.len() {
+                continue;
+            }
+            if let Some(used_input) = self.remote_inputs_used.get(tick) {
+                if *used_input != input {
+                    self.mispredicted_tick =
+                        Some(self.mispredicted_tick.map_or(tick, |first| first.min(tick)));
+                }
+            }
+            self.remote_inputs.push(input);
+        }
+    }
+
+    fn send(&mut self, packet: &Packet) {
+        if self.loss_rng.gen::<f32>() < self.conditions.packet_loss {
+            return;
+        }
+        self.delayed
+            .push_back((Instant::now() + self.conditions.latency, packet.encode()));
+        self.flush_delayed();
+    }
+
+    fn flush_delayed(&mut self) {
+        let now = Instant::now();
+        while let Some((send_at, _)) = self.delayed.front() {
+            if *send_at > now {
+                break;
+            }
+            if let Some((_, bytes)) = self.delayed.pop_front() {
+                // A packet that can not be sent is lost like any other UDP packet
+                let _ = self.socket.send_to(&bytes, self.peer);
+            }
+        }
+    }
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut
+}
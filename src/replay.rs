@@ -0,0 +1,176 @@
+use crate::{
+    constant::{MAX_PLAYERS, REPLAY_MAGIC, REPLAY_VERSION},
+    resource::{PlayerInput, PlayerInputs},
+};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Everything that is needed to play a run again: the seed of the random number generator, the
+/// settings that change the simulation and the players' input of every tick.
+///
+/// A replay file starts with a 4 bytes magic number, the format's version as a `u16`, the seed as
+/// a `u64`, the number of players that were in the run from the start as a `u8` and the god mode
+/// as a `u8` that is 0 or 1. It is followed by the number of runs as a `u32`, and by the runs
+/// themselves. A run
+/// is a `u32` number of ticks and the input of every player that was held during those ticks, as
+/// a `u8` for the actions and an `i8` for each axis of the stick. All the numbers are little
+/// endian.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    /// Players that were in the run from the start
+    pub players: usize,
+    pub god_mode: bool,
+    pub inputs: Vec<PlayerInputs>,
+}
+
+/// Errors that make a replay file unusable
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(PathBuf, io::Error),
+    NotAReplay,
+    VersionMismatch { found: u16, expected: u16 },
+    InvalidHeader,
+    Truncated,
+    TrailingBytes,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(path, err) => write!(f, "could not access {}: {}", path.display(), err),
+            ReplayError::NotAReplay => write!(f, "the file is not a replay"),
+            ReplayError::VersionMismatch { found, expected } => write!(
+                f,
+                "the replay was recorded with format version {} but this game reads version {}",
+                found, expected
+            ),
+            ReplayError::InvalidHeader => write!(f, "the replay's settings are not valid"),
+            ReplayError::Truncated => write!(f, "the replay ends unexpectedly"),
+            ReplayError::TrailingBytes => write!(f, "the replay has data after its last run"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn new(seed: u64, players: usize, god_mode: bool) -> Self {
+        Self {
+            seed,
+            players,
+            god_mode,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Read a replay file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|err| ReplayError::Io(path.to_path_buf(), err))?;
+        Self::decode(&bytes)
+    }
+
+    /// Write the replay to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        fs::write(path, self.encode()).map_err(|err| ReplayError::Io(path.to_path_buf(), err))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut runs: Vec<(u32, PlayerInputs)> = Vec::new();
+        for &input in self.inputs.iter() {
+            match runs.last_mut() {
+                Some((length, run_input)) if *run_input == input && *length < u32::MAX => {
+                    *length += 1
+                }
+                _ => runs.push((1, input)),
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(20 + runs.len() * (4 + 3 * MAX_PLAYERS));
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.players as u8);
+        bytes.push(self.god_mode as u8);
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (length, inputs) in runs {
+            bytes.extend_from_slice(&length.to_le_bytes());
+            for input in inputs.0.iter() {
+                bytes.push(input.bits());
+                bytes.extend(input.raw_stick().iter().map(|axis| *axis as u8));
+            }
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = ByteReader { bytes };
+        if reader.take(REPLAY_MAGIC.len()).ok() != Some(&REPLAY_MAGIC[..]) {
+            return Err(ReplayError::NotAReplay);
+        }
+        let version = u16::from_le_bytes(reader.take_array()?);
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::VersionMismatch {
+                found: version,
+                expected: REPLAY_VERSION,
+            });
+        }
+
+        let seed = u64::from_le_bytes(reader.take_array()?);
+        let [players, god_mode] = reader.take_array::<[u8; 2]>()?;
+        let players = players as usize;
+        if players == 0 || players > MAX_PLAYERS || god_mode > 1 {
+            return Err(ReplayError::InvalidHeader);
+        }
+        let run_count = u32::from_le_bytes(reader.take_array()?);
+        let mut inputs = Vec::new();
+        for _ in 0..run_count {
+            let length = u32::from_le_bytes(reader.take_array()?);
+            let mut run_inputs = PlayerInputs::default();
+            for input in run_inputs.0.iter_mut() {
+                let [bits, stick_x, stick_y] = reader.take_array::<[u8; 3]>()?;
+                *input = PlayerInput::from_raw(bits, [stick_x as i8, stick_y as i8]);
+            }
+            inputs.extend((0..length).map(|_| run_inputs));
+        }
+        if !reader.bytes.is_empty() {
+            return Err(ReplayError::TrailingBytes);
+        }
+
+        Ok(Self {
+            seed,
+            players,
+            god_mode: god_mode == 1,
+            inputs,
+        })
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ReplayError> {
+        if self.bytes.len() < count {
+            return Err(ReplayError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_array<T>(&mut self) -> Result<T, ReplayError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let mut array = T::default();
+        let count = array.as_mut().len();
+        array.as_mut().copy_from_slice(self.take(count)?);
+        Ok(array)
+    }
+}
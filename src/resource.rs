@@ -1,15 +1,33 @@
 use crate::{
+    atlas::{asset_path, SheetLayout, SpriteAtlases},
+    collision::{Shape, SpatialGrid},
+    components::{Collider, CollisionLayer, Player},
+    config::{AccessibilityConfig, GameConfig, PoolConfig},
     constant::{
-        ENEMY_BIG_SPRITE_HEIGHT, ENEMY_BIG_SPRITE_WIDTH, ENEMY_MEDIUM_SPRITE_HEIGHT,
-        ENEMY_MEDIUM_SPRITE_WIDTH, ENEMY_SMALL_SPRITE_HEIGHT, ENEMY_SMALL_SPRITE_WIDTH,
-        EXPLOSION_SPRITE_HEIGHT, EXPLOSION_SPRITE_WIDTH, SHIP_SPRITE_HEIGHT, SHIP_SPRITE_WIDTH,
+        ARENA_SCALE, COLLISION_GRID_CELL_SIZE, CONFIG_PATH, CONFIG_POLL_INTERVAL,
+        FIXED_TIMESTEP_RATE, MAX_FIXED_STEPS_PER_FRAME, MAX_PLAYERS, REBIND_CONFIRM_KEY,
+        SETTINGS_PATH,
     },
     events::{
-        CollisionLaserEnemyEvent, CollisionLaserShipEvent, SpawnEnemyEvent, SpawnExplosionEvent,
-        SpawnLaserEvent,
+        AnimationFinishedEvent, CollisionLaserEnemyEvent, CollisionLaserShipEvent,
+        ConfigReloadedEvent, ReportEvent, SpawnEnemyEvent, SpawnExplosionEvent, SpawnLaserEvent,
     },
+    input::{key_name, Action, InputBindings},
+    net::NetSession,
+    replay::Replay,
 };
-use bevy::prelude::*;
+use bevy::{
+    app::AppExit,
+    input::{
+        gamepad::{Gamepad, GamepadEvent},
+        keyboard::KeyCode,
+    },
+    prelude::*,
+    window::CursorMoved,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::PathBuf, time::Duration, time::SystemTime};
 
 #[derive(Default)]
 pub struct EventReaders {
@@ -17,16 +35,893 @@ pub struct EventReaders {
     pub collision_laser_enemy: EventReader<CollisionLaserEnemyEvent>,
     pub spawn_enemy: EventReader<SpawnEnemyEvent>,
     pub spawn_explosion: EventReader<SpawnExplosionEvent>,
+    pub explosion_feedback: EventReader<SpawnExplosionEvent>,
+    pub animation_finished: EventReader<AnimationFinishedEvent>,
     pub spawn_laser: EventReader<SpawnLaserEvent>,
+    pub config_reloaded: EventReader<ConfigReloadedEvent>,
+    pub accessibility_reloaded: EventReader<ConfigReloadedEvent>,
+    pub app_exit: EventReader<AppExit>,
+    pub pool_report: EventReader<AppExit>,
+    pub reports: EventReader<ReportEvent>,
+    pub gamepad: EventReader<GamepadEvent>,
+    pub cursor_moved: EventReader<CursorMoved>,
+}
+
+/// Options for players who are sensitive to some of the game's effects, they are taken from the
+/// config
+pub struct AccessibilitySettings {
+    pub camera_shake: bool,
+}
+
+impl AccessibilitySettings {
+    pub fn new(accessibility_config: &AccessibilityConfig) -> Self {
+        Self {
+            camera_shake: accessibility_config.camera_shake,
+        }
+    }
+}
+
+impl FromResources for AccessibilitySettings {
+    fn from_resources(resources: &Resources) -> Self {
+        Self::new(
+            &resources
+                .get::<GameConfig>()
+                .expect("Could not get config")
+                .accessibility,
+        )
+    }
+}
+
+/// Settings of a run that are chosen when the game starts
+pub struct GameSettings {
+    /// Size of an arena unit in pixels when the window opens, the window can be resized after that
+    pub scale: f32,
+    /// Players that are in the run from the start, the others can join later by pressing fire
+    pub players: usize,
+    /// The ship follows the mouse cursor instead of being moved in a direction
+    pub pointer_control: bool,
+    /// The ship ignores the lasers that hit it
+    pub god_mode: bool,
+    pub config_path: PathBuf,
+    /// Where the input bindings are saved after they have been changed
+    pub bindings_path: PathBuf,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            scale: ARENA_SCALE,
+            players: 1,
+            pointer_control: false,
+            god_mode: false,
+            config_path: asset_path(CONFIG_PATH),
+            bindings_path: asset_path(SETTINGS_PATH),
+        }
+    }
+}
+
+/// Size of the arena in arena units, its center is at the origin. It is taken from the config when
+/// the game starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Arena {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    /// Size of an arena unit in pixels that shows the whole arena in a window of the given size.
+    /// The size is a whole number of pixels so every pixel of a sprite is drawn as a square of the
+    /// same size, unless the window is too small to show the arena at one pixel per unit.
+    pub fn pixel_scale(&self, window_size: Vec2) -> f32 {
+        let fit = (window_size.x() / self.width).min(window_size.y() / self.height);
+        if fit >= 1. {
+            fit.floor()
+        } else {
+            fit
+        }
+    }
+}
+
+impl FromResources for Arena {
+    fn from_resources(resources: &Resources) -> Self {
+        let (width, height) = resources
+            .get::<GameConfig>()
+            .expect("Could not get config")
+            .arena;
+        Self::new(width, height)
+    }
 }
 
-pub struct TextureAtlasHandles {
-    pub ship: Handle<TextureAtlas>,
-    pub enemy_small: Handle<TextureAtlas>,
-    pub enemy_medium: Handle<TextureAtlas>,
-    pub enemy_big: Handle<TextureAtlas>,
-    pub laser_bolts: Handle<TextureAtlas>,
-    pub explosion: Handle<TextureAtlas>,
+/// How the arena is shown in the window, it follows the window's size
+pub struct ArenaView {
+    /// Size of an arena unit in pixels
+    pub scale: f32,
+    /// Size of the window that the scale was picked for
+    pub window_size: Vec2,
+}
+
+impl FromResources for ArenaView {
+    fn from_resources(resources: &Resources) -> Self {
+        Self {
+            scale: resources
+                .get::<GameSettings>()
+                .expect("Could not get game settings")
+                .scale,
+            window_size: Vec2::zero(),
+        }
+    }
+}
+
+/// The random number generator that every random decision of the game draws from, so that a run
+/// can be reproduced from its seed
+#[derive(Clone)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Keeps track of the config file's modification time so it can be reloaded when it changes
+pub struct ConfigWatcher {
+    pub path: PathBuf,
+    pub last_modified: Option<SystemTime>,
+    pub poll_timer: Timer,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            last_modified: fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            path,
+            poll_timer: Timer::new(CONFIG_POLL_INTERVAL, true),
+        }
+    }
+}
+
+/// The player's input for one tick. Every action is a bit and the tilt of the stick is kept as
+/// a byte per axis, so the input is cheap to record.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerInput {
+    bits: u8,
+    stick: [i8; 2],
+}
+
+impl PlayerInput {
+    pub const LEFT: u8 = 1;
+    pub const RIGHT: u8 = 1 << 1;
+    pub const UP: u8 = 1 << 2;
+    pub const DOWN: u8 = 1 << 3;
+    pub const FIRE: u8 = 1 << 4;
+    pub const BOMB: u8 = 1 << 5;
+    pub const FOCUS: u8 = 1 << 6;
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            bits,
+            stick: [0; 2],
+        }
+    }
+
+    pub fn from_raw(bits: u8, stick: [i8; 2]) -> Self {
+        Self { bits, stick }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    pub fn raw_stick(self) -> [i8; 2] {
+        self.stick
+    }
+
+    pub fn pressed(self, action: u8) -> bool {
+        self.bits & action != 0
+    }
+
+    pub fn set(&mut self, action: u8, pressed: bool) {
+        if pressed {
+            self.bits |= action;
+        } else {
+            self.bits &= !action;
+        }
+    }
+
+    /// Tilt of the stick, each axis is between -1 and 1
+    pub fn stick(self) -> Vec2 {
+        Vec2::new(self.stick[0] as f32, self.stick[1] as f32) / i8::MAX as f32
+    }
+
+    /// Set the tilt of the stick, it is shortened to a length of 1 and rounded to the precision
+    /// that is recorded
+    pub fn set_stick(&mut self, stick: Vec2) {
+        let stick = if stick.length() > 1. {
+            stick.normalize()
+        } else {
+            stick
+        };
+        let quantize = |value: f32| (value.max(-1.).min(1.) * i8::MAX as f32).round() as i8;
+        self.stick = [quantize(stick.x()), quantize(stick.y())];
+    }
+}
+
+/// The input of every player for one tick, indexed by `Player`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerInputs(pub [PlayerInput; MAX_PLAYERS]);
+
+impl PlayerInputs {
+    pub fn get(&self, player: Player) -> PlayerInput {
+        self.0.get(player.0).copied().unwrap_or_default()
+    }
+}
+
+/// Lives, bombs and score of a player that has joined the run
+#[derive(Debug, Clone)]
+pub struct PlayerState {
+    pub lives: u32,
+    pub score: u32,
+    /// Bombs left to the player's current ship
+    pub bombs: u32,
+    /// The bomb button was held during the previous tick, it has to be let go before the next
+    /// bomb is dropped
+    pub bomb_held: bool,
+    /// Counts down to the arrival of the player's next ship after their ship was destroyed
+    pub respawn_timer: Option<Timer>,
+}
+
+impl PlayerState {
+    pub fn new(lives: u32, bombs: u32) -> Self {
+        Self {
+            lives,
+            score: 0,
+            bombs,
+            bomb_held: false,
+            respawn_timer: None,
+        }
+    }
+}
+
+/// Every player that can be in the run, indexed by `Player`. Players that have not joined have no
+/// state.
+#[derive(Debug, Default, Clone)]
+pub struct Players(pub [Option<PlayerState>; MAX_PLAYERS]);
+
+impl Players {
+    pub fn get_mut(&mut self, player: Player) -> Option<&mut PlayerState> {
+        self.0.get_mut(player.0).and_then(Option::as_mut)
+    }
+
+    /// Check if every player that has joined has lost all of their lives
+    pub fn all_out(&self) -> bool {
+        self.0.iter().flatten().all(|state| state.lives == 0)
+    }
+}
+
+/// The players as they were when they were last reported, nothing has been reported before the
+/// first frame
+#[derive(Debug, Default)]
+pub struct AnnouncedPlayers(pub Option<Players>);
+
+/// Where the player's input comes from
+pub enum InputSource {
+    /// The keyboard and the gamepads
+    Devices,
+    /// The devices, the input of every tick is kept so it can be saved as a replay when the game
+    /// exits
+    Recording { path: PathBuf, replay: Replay },
+    /// The input of a replay, one tick after the other
+    Playback { replay: Replay, tick: usize },
+    /// The input is set directly on the `PlayerInputs` resource, e.g. by a bot or by a test
+    Scripted,
+    /// The devices for the local player, the other player's input comes over the network
+    Network(NetSession),
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::Devices
+    }
+}
+
+/// The mouse cursor, that the first player's ship follows when it is controlled with the pointer
+#[derive(Debug, Default)]
+pub struct Pointer {
+    /// Position of the cursor in arena units, it is unknown until the cursor moves over the window
+    pub position: Option<Vec2>,
+    /// Tilt of a stick that would move the ship toward the cursor
+    pub tilt: Vec2,
+    pub fire: bool,
+    pub bomb: bool,
+}
+
+/// The gamepad of every player. A player keeps their gamepad's slot when it is unplugged, the
+/// slot stays empty until that gamepad or a new one is plugged in. A gamepad that is plugged in
+/// while every slot is taken is not used.
+#[derive(Debug, Default)]
+pub struct ConnectedGamepads {
+    slots: [Option<Gamepad>; MAX_PLAYERS],
+    /// The gamepad that was last in each slot, it gets its slot back when it is plugged in again
+    previous: [Option<Gamepad>; MAX_PLAYERS],
+}
+
+impl ConnectedGamepads {
+    pub fn gamepad(&self, player: Player) -> Option<Gamepad> {
+        self.slots.get(player.0).copied().flatten()
+    }
+
+    /// Give the gamepad a slot, its previous slot if it is empty and the first empty slot
+    /// otherwise. Returns the player of the slot.
+    pub fn connect(&mut self, gamepad: Gamepad) -> Option<Player> {
+        if let Some(player_idx) = self.slots.iter().position(|slot| *slot == Some(gamepad)) {
+            return Some(Player(player_idx));
+        }
+        let player_idx = (0..MAX_PLAYERS)
+            .find(|idx| self.slots[*idx].is_none() && self.previous[*idx] == Some(gamepad))
+            .or_else(|| self.slots.iter().position(Option::is_none))?;
+        self.slots[player_idx] = Some(gamepad);
+        self.previous[player_idx] = Some(gamepad);
+        Some(Player(player_idx))
+    }
+
+    /// Empty the gamepad's slot. Returns the player of the slot.
+    pub fn disconnect(&mut self, gamepad: Gamepad) -> Option<Player> {
+        let player_idx = self.slots.iter().position(|slot| *slot == Some(gamepad))?;
+        self.slots[player_idx] = None;
+        Some(Player(player_idx))
+    }
+}
+
+/// Progress through the rebinding of all the actions, one action after the other and one player
+/// after the other
+#[derive(Debug)]
+pub enum Rebinding {
+    Inactive,
+    Active {
+        player: Player,
+        action_idx: usize,
+        /// Keys pressed for the current action
+        keys: Vec<KeyCode>,
+        /// A key that is bound to another action, pressing it again moves it to the current action
+        conflicting_key: Option<KeyCode>,
+        bindings: InputBindings,
+        was_paused: bool,
+        /// What happened to the last key that was pressed
+        message: String,
+    },
+}
+
+impl Rebinding {
+    /// Lines of the rebinding screen: a title, one line per action and a message
+    pub const LINE_COUNT: usize = Action::ALL.len() + 2;
+
+    /// The text of every line of the rebinding screen, the lines are empty while nothing is being
+    /// rebound. Every action shows its keys, the current action shows the keys pressed so far once
+    /// one is pressed.
+    pub fn screen_lines(&self) -> Vec<String> {
+        let (player, action_idx, keys, bindings, message) = match self {
+            Rebinding::Inactive => return vec![String::new(); Self::LINE_COUNT],
+            Rebinding::Active {
+                player,
+                action_idx,
+                keys,
+                bindings,
+                message,
+                ..
+            } => (player, action_idx, keys, bindings, message),
+        };
+        let mut lines = Vec::with_capacity(Self::LINE_COUNT);
+        lines.push(format!(
+            "Player {}: press the keys for each action, then {:?}",
+            player.0 + 1,
+            REBIND_CONFIRM_KEY
+        ));
+        for (idx, action) in Action::ALL.iter().enumerate() {
+            let current = idx == *action_idx;
+            let action_keys = if current && !keys.is_empty() {
+                keys.as_slice()
+            } else {
+                bindings.keys(*player, *action)
+            };
+            let key_names: Vec<&str> = action_keys
+                .iter()
+                .filter_map(|key| key_name(*key))
+                .collect();
+            lines.push(format!(
+                "{} {:<11} {}",
+                if current { ">" } else { " " },
+                action.to_string(),
+                key_names.join(", ")
+            ));
+        }
+        lines.push(message.clone());
+        lines
+    }
+}
+
+impl Default for Rebinding {
+    fn default() -> Self {
+        Rebinding::Inactive
+    }
+}
+
+/// Accumulates the frames' time and turns it into a whole number of simulation ticks of the same
+/// length, what is left over is carried to the next frame
+#[derive(Debug)]
+pub struct FixedTimestep {
+    pub step_seconds: f32,
+    pub accumulator: f32,
+    /// Ticks that can be run in one frame, time beyond that is dropped so a slow frame does not
+    /// cause even slower frames
+    pub max_steps_per_frame: u32,
+    /// When this is false, the frames' time is ignored and every frame runs exactly one tick. Runs
+    /// without a window then go as fast as they can and can be stepped one tick at a time.
+    pub real_time: bool,
+    /// No tick is run while the game is paused
+    pub paused: bool,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self {
+            step_seconds: 1. / FIXED_TIMESTEP_RATE,
+            accumulator: 0.,
+            max_steps_per_frame: MAX_FIXED_STEPS_PER_FRAME,
+            real_time: true,
+            paused: false,
+        }
+    }
+}
+
+impl FixedTimestep {
+    /// Add the time of a frame, returns the number of ticks that have to be run
+    pub fn accumulate(&mut self, delta_seconds: f32) -> u32 {
+        if self.paused {
+            self.accumulator = 0.;
+            return 0;
+        }
+        if !self.real_time {
+            return 1;
+        }
+
+        self.accumulator += delta_seconds;
+        let mut steps = 0;
+        while self.accumulator >= self.step_seconds {
+            if steps == self.max_steps_per_frame {
+                self.accumulator = 0.;
+                break;
+            }
+            self.accumulator -= self.step_seconds;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// How far the time is between the last tick and the next one, in the range [0, 1)
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step_seconds
+    }
+}
+
+/// The time that drives the game world, it advances by one fixed step each simulation tick and
+/// stands still while the game is in hit-stop
+#[derive(Debug, Default, Clone)]
+pub struct GameTime {
+    pub delta_seconds: f32,
+}
+
+/// The pools of the entities that come and go often, so they can be reused instead of spawned
+/// again with a new sprite. The pools are not part of the snapshots, so an entity that a rollback
+/// brought back can still be in a pool and has to be checked before it is taken out.
+pub struct EntityPools {
+    pub lasers: EntityPool,
+    pub explosions: EntityPool,
+}
+
+impl EntityPools {
+    pub fn new(config: &PoolConfig) -> Self {
+        Self {
+            lasers: EntityPool::new(config.lasers),
+            explosions: EntityPool::new(config.explosions),
+        }
+    }
+
+    pub fn set_capacities(&mut self, config: &PoolConfig) {
+        self.lasers.set_capacity(config.lasers);
+        self.explosions.set_capacity(config.explosions);
+    }
+}
+
+impl FromResources for EntityPools {
+    fn from_resources(resources: &Resources) -> Self {
+        Self::new(
+            &resources
+                .get::<GameConfig>()
+                .expect("Could not get config")
+                .pools,
+        )
+    }
+}
+
+/// Entities of one kind that are out of the game world and free to be reused, up to a capacity.
+/// Taking an entity out of the pool is a hit, finding the pool empty is a miss.
+#[derive(Debug)]
+pub struct EntityPool {
+    free: Vec<Entity>,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl EntityPool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            free: Vec::with_capacity(capacity),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Take a free entity out of the pool, the ones that `is_free` rejects are dropped from the
+    /// pool. `None` means that the pool is exhausted and a new entity has to be spawned.
+    pub fn acquire(&mut self, is_free: impl Fn(Entity) -> bool) -> Option<Entity> {
+        while let Some(entity) = self.free.pop() {
+            if is_free(entity) {
+                self.hits += 1;
+                return Some(entity);
+            }
+        }
+        self.misses += 1;
+        None
+    }
+
+    /// Put an entity back in the pool, `false` means that the pool is full and the entity has to
+    /// be despawned
+    pub fn release(&mut self, entity: Entity) -> bool {
+        if self.free.contains(&entity) {
+            return true;
+        }
+        if self.free.len() >= self.capacity {
+            return false;
+        }
+        self.free.push(entity);
+        true
+    }
+
+    /// Change how many entities the pool keeps, the ones that are already in it stay there until
+    /// they are reused
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Number of entities that are waiting to be reused
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+/// Where the entities that collide are in the arena, with a grid for each collision layer. It is
+/// filled again every tick.
+pub struct CollisionGrids {
+    bodies: Vec<CollisionBody>,
+    /// Shapes of all the bodies, each body has a range of them
+    shapes: Vec<Shape>,
+    layers: Vec<SpatialGrid<usize>>,
+    /// Bodies that a query has found in one layer, kept so queries do not allocate
+    near: Vec<usize>,
+}
+
+/// An entity in the collision grids, with the box that contains its shapes now and before the
+/// tick moved them
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionBody {
+    pub entity: Entity,
+    pub layer: CollisionLayer,
+    /// Where the entity is at the end of the tick
+    pub position: Vec2,
+    /// How far the entity has moved during the tick
+    pub motion: Vec2,
+    /// The entity is tested along the way it moved instead of only where it is now
+    pub continuous: bool,
+    first_shape: usize,
+    shape_count: usize,
+    bounds_center: Vec2,
+    bounds_size: Vec2,
+}
+
+impl FromResources for CollisionGrids {
+    fn from_resources(resources: &Resources) -> Self {
+        let arena = resources.get::<Arena>().expect("Could not get arena");
+        Self {
+            bodies: Vec::new(),
+            shapes: Vec::new(),
+            layers: (0..CollisionLayer::COUNT)
+                .map(|_| SpatialGrid::new(Vec2::zero(), arena.size(), COLLISION_GRID_CELL_SIZE))
+                .collect(),
+            near: Vec::new(),
+        }
+    }
+}
+
+impl CollisionGrids {
+    pub fn clear(&mut self) {
+        self.bodies.clear();
+        self.shapes.clear();
+        for grid in self.layers.iter_mut() {
+            grid.clear();
+        }
+    }
+
+    /// Add an entity with its collider at the given position to the grids of the layers it is on.
+    /// The motion is how far the entity has moved to get there during the tick.
+    pub fn insert(
+        &mut self,
+        entity: Entity,
+        layer: CollisionLayer,
+        collider: &Collider,
+        position: Vec2,
+        motion: Vec2,
+        continuous: bool,
+    ) {
+        let first_shape = self.shapes.len();
+        collider.shapes(position, &mut self.shapes);
+        let shapes = &self.shapes[first_shape..];
+        if shapes.is_empty() {
+            return;
+        }
+
+        let (mut min, mut max) = (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY));
+        // Two entities that overlap at any time during the tick have overlapping boxes
+        for shape in shapes.iter() {
+            let (center, size) = shape.swept(motion).bounds();
+            min = min.min(center - size / 2.);
+            max = max.max(center + size / 2.);
+        }
+        let body = CollisionBody {
+            entity,
+            layer,
+            position,
+            motion,
+            continuous,
+            first_shape,
+            shape_count: shapes.len(),
+            bounds_center: (min + max) / 2.,
+            bounds_size: max - min,
+        };
+
+        let body_idx = self.bodies.len();
+        for (layer_idx, grid) in self.layers.iter_mut().enumerate() {
+            if body.layer.is_on(1 << layer_idx) {
+                grid.insert(body_idx, body.bounds_center, body.bounds_size);
+            }
+        }
+        self.bodies.push(body);
+    }
+
+    pub fn bodies(&self) -> &[CollisionBody] {
+        &self.bodies
+    }
+
+    /// Get the bodies that were added after the given one, that it collides with by their layers
+    /// and that are close enough to overlap it. Every pair of bodies is only found once, from the
+    /// body that was added first.
+    pub fn query(&mut self, body_idx: usize, found: &mut Vec<usize>) {
+        found.clear();
+        let body = self.bodies[body_idx];
+        for (layer_idx, grid) in self.layers.iter_mut().enumerate() {
+            if body.layer.mask & (1 << layer_idx) != 0 {
+                grid.query(body.bounds_center, body.bounds_size, &mut self.near);
+                found.extend_from_slice(&self.near);
+            }
+        }
+        // Bodies that are on more than one of the layers are found more than once
+        found.sort_unstable();
+        found.dedup();
+        let bodies = &self.bodies;
+        found.retain(|other_idx| {
+            *other_idx > body_idx && body.layer.collides_with(bodies[*other_idx].layer)
+        });
+    }
+
+    /// When the two bodies are the closest to each other during the tick, as a fraction of the
+    /// tick. It is taken from how their positions moved relative to each other, so bodies that
+    /// did not move are all the closest at the start of the tick.
+    pub fn time_of_impact(&self, body_idx: usize, other_idx: usize) -> f32 {
+        let (body, other) = (&self.bodies[body_idx], &self.bodies[other_idx]);
+        let motion = body.motion - other.motion;
+        let motion_length_squared = motion.length_squared();
+        if motion_length_squared <= 0. {
+            return 0.;
+        }
+        let start_offset = (other.position - other.motion) - (body.position - body.motion);
+        (start_offset.dot(motion) / motion_length_squared)
+            .max(0.)
+            .min(1.)
+    }
+
+    /// Check if a shape of one body intersects a shape of the other. When one of them is
+    /// continuous, its shapes are swept by how it moved compared to the other one, so it hits
+    /// what it went through during the tick.
+    pub fn intersect(&self, body_idx: usize, other_idx: usize) -> bool {
+        let shapes = |body: &CollisionBody| {
+            &self.shapes[body.first_shape..body.first_shape + body.shape_count]
+        };
+        let (body, other) = (&self.bodies[body_idx], &self.bodies[other_idx]);
+        let (swept, still) = if other.continuous && !body.continuous {
+            (other, body)
+        } else {
+            (body, other)
+        };
+        let motion = if swept.continuous {
+            swept.motion - still.motion
+        } else {
+            Vec2::zero()
+        };
+        let still_shapes = shapes(still);
+        shapes(swept).iter().any(|shape| {
+            let shape = shape.swept(motion);
+            still_shapes
+                .iter()
+                .any(|still_shape| shape.intersects(still_shape))
+        })
+    }
+}
+
+/// Freezes the game world for a brief moment to emphasize heavy impacts
+#[derive(Debug, Default, Clone)]
+pub struct HitStop {
+    pub remaining_seconds: f32,
+}
+
+/// How a clip continues once its last frame has been shown
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+pub enum AnimationMode {
+    /// Stay on the last frame and report that the clip has finished
+    Once,
+    /// Start over from the first frame
+    Loop,
+    /// Go back to the first frame in reverse order, then forward again
+    PingPong,
+}
+
+/// An index and how long it is shown. The index is a sprite index when the clip is played on its
+/// own, or the channel's value when the clip is played in a channel of a layered animation.
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationFrame {
+    pub index: u32,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    /// Sheet of the atlas manifest that the frames are taken from
+    pub sheet: String,
+    pub frames: Vec<AnimationFrame>,
+    pub mode: AnimationMode,
+}
+
+impl AnimationClip {
+    /// Create a clip whose frames are all shown for the same duration
+    pub fn uniform(
+        sheet: &str,
+        indices: &[u32],
+        frame_duration: Duration,
+        mode: AnimationMode,
+    ) -> Self {
+        Self::new(
+            sheet,
+            indices
+                .iter()
+                .map(|&index| AnimationFrame {
+                    index,
+                    duration: frame_duration,
+                })
+                .collect(),
+            mode,
+        )
+    }
+
+    pub fn new(sheet: &str, frames: Vec<AnimationFrame>, mode: AnimationMode) -> Self {
+        assert!(!frames.is_empty(), "Animation clip has no frame");
+        assert!(
+            frames
+                .iter()
+                .all(|frame| frame.duration > Duration::default()),
+            "Animation clip has a frame with no duration"
+        );
+        Self {
+            sheet: sheet.to_string(),
+            frames,
+            mode,
+        }
+    }
+}
+
+/// All the animation clips that can be played, indexed by their name
+#[derive(Debug, Default, Clone)]
+pub struct AnimationClips(HashMap<String, AnimationClip>);
+
+impl AnimationClips {
+    pub fn get(&self, name: &str) -> Option<&AnimationClip> {
+        self.0.get(name)
+    }
+
+    pub fn insert(&mut self, name: &str, clip: AnimationClip) {
+        self.0.insert(name.to_string(), clip);
+    }
+}
+
+impl FromResources for AnimationClips {
+    /// The clips are declared in the atlas manifest, next to the sheets they are played on
+    fn from_resources(resources: &Resources) -> Self {
+        resources
+            .get::<SpriteAtlases>()
+            .expect("Could not get sprite atlases")
+            .clips()
+            .clone()
+    }
+}
+
+/// Handles to the texture atlases of all the sheets declared in the atlas manifest
+pub struct TextureAtlasHandles(HashMap<String, Handle<TextureAtlas>>);
+
+impl TextureAtlasHandles {
+    pub fn get(&self, sheet: &str) -> Option<&Handle<TextureAtlas>> {
+        self.0.get(sheet)
+    }
 }
 
 impl FromResources for TextureAtlasHandles {
@@ -39,85 +934,40 @@ impl FromResources for TextureAtlasHandles {
             .get_mut::<Assets<TextureAtlas>>()
             .expect("Could not get texture atlas asset");
 
-        // SHIP SPRITESHEET
-        let texture_atlas = TextureAtlas::from_grid(
-            asset_server.load("spritesheets/ship.png"),
-            Vec2::new(SHIP_SPRITE_WIDTH, SHIP_SPRITE_HEIGHT),
-            5,
-            2,
-        );
-        let ship = texture_atlases.add(texture_atlas);
-
-        // SMALL ENEMY SPRITESHEET
-        let texture_atlas = TextureAtlas::from_grid(
-            asset_server.load("spritesheets/enemy-small.png"),
-            Vec2::new(ENEMY_SMALL_SPRITE_WIDTH, ENEMY_SMALL_SPRITE_HEIGHT),
-            2,
-            1,
-        );
-        let enemy_small = texture_atlases.add(texture_atlas);
-
-        // MEDIUM ENEMY SPRITESHEET
-        let texture_atlas = TextureAtlas::from_grid(
-            asset_server.load("spritesheets/enemy-medium.png"),
-            Vec2::new(ENEMY_MEDIUM_SPRITE_WIDTH, ENEMY_MEDIUM_SPRITE_HEIGHT),
-            2,
-            1,
-        );
-        let enemy_medium = texture_atlases.add(texture_atlas);
-
-        // BIG ENEMY SPRITESHEET
-        let texture_atlas = TextureAtlas::from_grid(
-            asset_server.load("spritesheets/enemy-big.png"),
-            Vec2::new(ENEMY_BIG_SPRITE_WIDTH, ENEMY_BIG_SPRITE_HEIGHT),
-            2,
-            1,
-        );
-        let enemy_big = texture_atlases.add(texture_atlas);
-
-        // LASER SPRITESHEET
-        let mut texture_atlas = TextureAtlas::new_empty(
-            asset_server.load("spritesheets/laser-bolts.png"),
-            Vec2::new(32., 32.),
-        );
-        // enemy_laser_01
-        texture_atlas.add_texture(bevy::sprite::Rect {
-            min: Vec2::new(6., 7.),
-            max: Vec2::new(11., 12.),
-        });
-        // ship_laser_01
-        texture_atlas.add_texture(bevy::sprite::Rect {
-            min: Vec2::new(6., 18.),
-            max: Vec2::new(11., 30.),
-        });
-        // enemy_laser_02
-        texture_atlas.add_texture(bevy::sprite::Rect {
-            min: Vec2::new(20., 7.),
-            max: Vec2::new(25., 12.),
-        });
-        // ship_laser_02
-        texture_atlas.add_texture(bevy::sprite::Rect {
-            min: Vec2::new(20., 18.),
-            max: Vec2::new(25., 31.),
-        });
-        let laser_bolts = texture_atlases.add(texture_atlas);
-
-        // EXPLOSION SPRITESHEET
-        let texture_atlas = TextureAtlas::from_grid(
-            asset_server.load("spritesheets/explosion.png"),
-            Vec2::new(EXPLOSION_SPRITE_WIDTH, EXPLOSION_SPRITE_HEIGHT),
-            5,
-            1,
-        );
-        let explosion = texture_atlases.add(texture_atlas);
+        let sprite_atlases = resources
+            .get::<SpriteAtlases>()
+            .expect("Could not get sprite atlases");
 
-        Self {
-            ship,
-            enemy_small,
-            enemy_medium,
-            enemy_big,
-            laser_bolts,
-            explosion,
+        let mut handles = HashMap::new();
+        for (sheet, definition) in sprite_atlases.sheets() {
+            let texture = asset_server.load(definition.texture.as_str());
+            let texture_atlas = match &definition.layout {
+                SheetLayout::Grid {
+                    tile_size,
+                    columns,
+                    rows,
+                    ..
+                } => TextureAtlas::from_grid(
+                    texture,
+                    Vec2::new(tile_size.0, tile_size.1),
+                    *columns,
+                    *rows,
+                ),
+                SheetLayout::Regions { size, regions } => {
+                    let mut texture_atlas =
+                        TextureAtlas::new_empty(texture, Vec2::new(size.0, size.1));
+                    for region in regions.iter() {
+                        texture_atlas.add_texture(bevy::sprite::Rect {
+                            min: Vec2::new(region.min.0, region.min.1),
+                            max: Vec2::new(region.max.0, region.max.1),
+                        });
+                    }
+                    texture_atlas
+                }
+            };
+            handles.insert(sheet.clone(), texture_atlases.add(texture_atlas));
         }
+
+        Self(handles)
     }
 }
@@ -0,0 +1,317 @@
+use crate::{
+    components::{
+        Animation, ArenaBounds, Collider, CollisionLayer, ContinuousCollision, Enemy, EnemySpawner,
+        Explosion, HitBox, Laser, LayeredAnimation, Player, Pooled, PreviousTranslation,
+        SheetSprite, Ship, TimeToLive, Velocity, Weapon,
+    },
+    events::{
+        AnimationFinishedEvent, CollisionLaserEnemyEvent, CollisionLaserShipEvent, SpawnEnemyEvent,
+        SpawnExplosionEvent, SpawnLaserEvent,
+    },
+    resource::{EventReaders, GameRng, GameTime, HitStop, Players},
+};
+use bevy::{
+    ecs::{Component, Resource},
+    prelude::*,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Everything that the next tick of the game world depends on. Restoring a snapshot puts the game
+/// world back to how it was when the snapshot was taken, so the ticks from there can be run again.
+pub struct Snapshot {
+    entities: Vec<EntitySnapshot>,
+    rng: GameRng,
+    game_time: GameTime,
+    hit_stop: HitStop,
+    players: Players,
+    spawn_enemy_events: Vec<SpawnEnemyEvent>,
+    spawn_laser_events: Vec<SpawnLaserEvent>,
+    spawn_explosion_events: Vec<SpawnExplosionEvent>,
+}
+
+impl Snapshot {
+    /// Copy the game world in between two ticks. The gameplay events that the next tick has yet to
+    /// read are sent again on their own, so the next tick runs the same way whether the snapshot is
+    /// restored before it or not.
+    pub fn take(world: &World, resources: &mut Resources) -> Self {
+        let mut snapshot = Self {
+            entities: gameplay_entities(world)
+                .into_iter()
+                .map(|entity| EntitySnapshot::take(world, entity))
+                .collect(),
+            rng: resources
+                .get::<GameRng>()
+                .expect("Could not get game rng")
+                .clone(),
+            game_time: resources
+                .get::<GameTime>()
+                .expect("Could not get game time")
+                .clone(),
+            hit_stop: resources
+                .get::<HitStop>()
+                .expect("Could not get hit stop")
+                .clone(),
+            players: resources
+                .get::<Players>()
+                .expect("Could not get players")
+                .clone(),
+            spawn_enemy_events: Vec::new(),
+            spawn_laser_events: Vec::new(),
+            spawn_explosion_events: Vec::new(),
+        };
+
+        {
+            let mut event_readers = resources
+                .get_mut::<EventReaders>()
+                .expect("Could not get event readers");
+            snapshot.spawn_enemy_events = event_readers
+                .spawn_enemy
+                .iter(
+                    &resources
+                        .get::<Events<SpawnEnemyEvent>>()
+                        .expect("Could not get spawn enemy events"),
+                )
+                .cloned()
+                .collect();
+            snapshot.spawn_laser_events = event_readers
+                .spawn_laser
+                .iter(
+                    &resources
+                        .get::<Events<SpawnLaserEvent>>()
+                        .expect("Could not get spawn laser events"),
+                )
+                .cloned()
+                .collect();
+            snapshot.spawn_explosion_events = event_readers
+                .spawn_explosion
+                .iter(
+                    &resources
+                        .get::<Events<SpawnExplosionEvent>>()
+                        .expect("Could not get spawn explosion events"),
+                )
+                .cloned()
+                .collect();
+        }
+        snapshot.resend_events(resources, &|entity| entity);
+        snapshot
+    }
+
+    /// Put the game world back to how it was when the snapshot was taken. The entities that have
+    /// been added since then are removed, and the ones that have been removed are added again as
+    /// new entities.
+    pub fn restore(&self, world: &mut World, resources: &mut Resources) {
+        let kept: HashSet<Entity> = self
+            .entities
+            .iter()
+            .map(|entity_snapshot| entity_snapshot.entity)
+            .collect();
+        for entity in gameplay_entities(world) {
+            if !kept.contains(&entity) {
+                world.despawn(entity).expect("Could not despawn entity");
+            }
+        }
+
+        let mut respawned = HashMap::new();
+        for entity_snapshot in self.entities.iter() {
+            if !world.contains(entity_snapshot.entity) {
+                let entity = world.spawn((GlobalTransform::default(),));
+                respawned.insert(entity_snapshot.entity, entity);
+            }
+        }
+        let remap = |entity: Entity| respawned.get(&entity).copied().unwrap_or(entity);
+        for entity_snapshot in self.entities.iter() {
+            entity_snapshot.restore(world, remap(entity_snapshot.entity), &remap);
+        }
+
+        *resources
+            .get_mut::<GameRng>()
+            .expect("Could not get game rng") = self.rng.clone();
+        *resources
+            .get_mut::<GameTime>()
+            .expect("Could not get game time") = self.game_time.clone();
+        *resources
+            .get_mut::<HitStop>()
+            .expect("Could not get hit stop") = self.hit_stop.clone();
+        *resources
+            .get_mut::<Players>()
+            .expect("Could not get players") = self.players.clone();
+        self.resend_events(resources, &remap);
+    }
+
+    /// The players' lives and scores when the snapshot was taken
+    pub fn players(&self) -> &Players {
+        &self.players
+    }
+
+    /// Replace the gameplay events with the ones that the next tick has yet to read, and rewind
+    /// the readers so that these are the only events they read
+    fn resend_events(&self, resources: &Resources, remap: &dyn Fn(Entity) -> Entity) {
+        let mut event_readers = resources
+            .get_mut::<EventReaders>()
+            .expect("Could not get event readers");
+
+        resend(resources, self.spawn_enemy_events.iter().cloned());
+        event_readers.spawn_enemy = Default::default();
+        resend(
+            resources,
+            self.spawn_laser_events.iter().map(|evt| SpawnLaserEvent {
+                laser_source: remap(evt.laser_source),
+                ..evt.clone()
+            }),
+        );
+        event_readers.spawn_laser = Default::default();
+        resend(resources, self.spawn_explosion_events.iter().cloned());
+        event_readers.spawn_explosion = Default::default();
+        // The impact of the explosions was felt by the tick that sent them
+        event_readers.explosion_feedback = Default::default();
+        for _ in event_readers.explosion_feedback.iter(
+            &resources
+                .get::<Events<SpawnExplosionEvent>>()
+                .expect("Could not get spawn explosion events"),
+        ) {}
+
+        // The other gameplay events are read by the same tick that sends them
+        resend::<AnimationFinishedEvent>(resources, None);
+        event_readers.animation_finished = Default::default();
+        resend::<CollisionLaserEnemyEvent>(resources, None);
+        event_readers.collision_laser_enemy = Default::default();
+        resend::<CollisionLaserShipEvent>(resources, None);
+        event_readers.collision_laser_ship = Default::default();
+    }
+}
+
+/// Copy of the components of a gameplay entity
+struct EntitySnapshot {
+    entity: Entity,
+    parent: Option<Entity>,
+    transform: Option<Transform>,
+    previous_translation: Option<PreviousTranslation>,
+    velocity: Option<Velocity>,
+    hit_box: Option<HitBox>,
+    collider: Option<Collider>,
+    collision_layer: Option<CollisionLayer>,
+    continuous_collision: Option<ContinuousCollision>,
+    sheet_sprite: Option<SheetSprite>,
+    animation: Option<Animation>,
+    layered_animation: Option<LayeredAnimation>,
+    time_to_live: Option<TimeToLive>,
+    arena_bounds: Option<ArenaBounds>,
+    ship: Option<Ship>,
+    player: Option<Player>,
+    weapon: Option<Weapon>,
+    enemy: Option<Enemy>,
+    enemy_spawner: Option<EnemySpawner>,
+    laser: Option<Laser>,
+    explosion: Option<Explosion>,
+    pooled: Option<Pooled>,
+}
+
+impl EntitySnapshot {
+    fn take(world: &World, entity: Entity) -> Self {
+        Self {
+            entity,
+            parent: get::<Parent>(world, entity).map(|parent| parent.0),
+            transform: get(world, entity),
+            previous_translation: get(world, entity),
+            velocity: get(world, entity),
+            hit_box: get(world, entity),
+            collider: get(world, entity),
+            collision_layer: get(world, entity),
+            continuous_collision: get(world, entity),
+            sheet_sprite: get(world, entity),
+            animation: get(world, entity),
+            layered_animation: get(world, entity),
+            time_to_live: get(world, entity),
+            arena_bounds: get(world, entity),
+            ship: get(world, entity),
+            player: get(world, entity),
+            weapon: get(world, entity),
+            enemy: get(world, entity),
+            enemy_spawner: get(world, entity),
+            laser: get(world, entity),
+            explosion: get(world, entity),
+            pooled: get(world, entity),
+        }
+    }
+
+    /// Put the components back on the entity that stands for the snapshot's entity now. The
+    /// entities that the components refer to are mapped the same way.
+    fn restore(&self, world: &mut World, entity: Entity, remap: &dyn Fn(Entity) -> Entity) {
+        insert(world, entity, &self.transform);
+        insert(world, entity, &self.previous_translation);
+        insert(world, entity, &self.velocity);
+        insert(world, entity, &self.hit_box);
+        insert(world, entity, &self.collider);
+        insert(world, entity, &self.collision_layer);
+        insert(world, entity, &self.continuous_collision);
+        insert(world, entity, &self.sheet_sprite);
+        insert(world, entity, &self.animation);
+        insert(world, entity, &self.layered_animation);
+        insert(world, entity, &self.time_to_live);
+        insert(world, entity, &self.arena_bounds);
+        insert(world, entity, &self.ship);
+        insert(world, entity, &self.player);
+        insert(world, entity, &self.weapon);
+        insert(world, entity, &self.enemy);
+        insert(world, entity, &self.enemy_spawner);
+        insert(world, entity, &self.explosion);
+        insert(world, entity, &self.pooled);
+        let laser = self.laser.as_ref().map(|laser| Laser {
+            source: remap(laser.source),
+            player: laser.player,
+        });
+        insert(world, entity, &laser);
+        // The parents get their children back when the hierarchy is updated
+        insert(
+            world,
+            entity,
+            &self.parent.map(|parent| Parent(remap(parent))),
+        );
+    }
+}
+
+/// Entities that are part of the game world, the cameras, the background and the entities that
+/// wait in a pool are left out
+fn gameplay_entities(world: &World) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    entities.extend(world.query::<(Entity, &Ship)>().map(|(entity, _)| entity));
+    entities.extend(world.query::<(Entity, &Weapon)>().map(|(entity, _)| entity));
+    entities.extend(world.query::<(Entity, &Enemy)>().map(|(entity, _)| entity));
+    entities.extend(
+        world
+            .query::<(Entity, &EnemySpawner)>()
+            .map(|(entity, _)| entity),
+    );
+    entities.extend(world.query::<(Entity, &Laser)>().map(|(entity, _)| entity));
+    entities.extend(
+        world
+            .query::<(Entity, &Explosion)>()
+            .map(|(entity, _)| entity),
+    );
+    entities
+}
+
+fn get<T: Component + Clone>(world: &World, entity: Entity) -> Option<T> {
+    world
+        .get::<T>(entity)
+        .ok()
+        .map(|component| (*component).clone())
+}
+
+fn insert<T: Component + Clone>(world: &mut World, entity: Entity, component: &Option<T>) {
+    if let Some(component) = component {
+        world
+            .insert_one(entity, component.clone())
+            .expect("Could not restore component");
+    }
+}
+
+fn resend<T: Resource>(resources: &Resources, pending: impl IntoIterator<Item = T>) {
+    let mut events = resources
+        .get_mut::<Events<T>>()
+        .expect("Could not get events");
+    *events = Events::default();
+    for evt in pending {
+        events.send(evt);
+    }
+}
@@ -1,14 +1,126 @@
-use crate::components::Animation;
+use crate::{
+    components::{Animation, LayeredAnimation, SheetSprite},
+    events::AnimationFinishedEvent,
+    resource::{AnimationClip, AnimationClips, AnimationMode, GameTime},
+};
 use bevy::prelude::*;
 
-/// Periodically change the index to the sprite in the spritesheet
-pub fn texture_atlas_cycle(
-    time: Res<Time>,
-    mut sprite: Mut<TextureAtlasSprite>,
+/// Advance the entities' animation clips and change the index of their sprite to the one of the
+/// current frame. An entity whose clip is not declared keeps its sprite.
+pub fn play_animation(
+    time: Res<GameTime>,
+    animation_clips: Res<AnimationClips>,
+    mut animation_finished_events: ResMut<Events<AnimationFinishedEvent>>,
+    entity: Entity,
     mut animation: Mut<Animation>,
+    mut sheet_sprite: Mut<SheetSprite>,
 ) {
-    animation.timer.tick(time.delta_seconds);
-    if animation.timer.finished {
-        sprite.index = (sprite.index + animation.idx_delta) % animation.sprite_count;
+    let clip = match animation_clips.get(&animation.clip) {
+        Some(clip) => clip,
+        None => return,
+    };
+
+    if advance_animation(clip, &mut animation, time.delta_seconds) {
+        animation_finished_events.send(AnimationFinishedEvent {
+            entity,
+            clip: animation.clip.clone(),
+        });
+    }
+    set_sheet(&mut sheet_sprite, clip);
+    sheet_sprite.index = clip.frames[animation.frame].index;
+}
+
+/// Advance the clips of all the channels, then combine the channels' current frames into the
+/// index of the sprite. A channel whose clip is not declared stays on its first value.
+pub fn play_layered_animation(
+    time: Res<GameTime>,
+    animation_clips: Res<AnimationClips>,
+    mut animation_finished_events: ResMut<Events<AnimationFinishedEvent>>,
+    entity: Entity,
+    mut layered_animation: Mut<LayeredAnimation>,
+    mut sheet_sprite: Mut<SheetSprite>,
+) {
+    let mut sprite_idx = 0;
+    for channel in layered_animation.channels.iter_mut() {
+        let clip = match animation_clips.get(&channel.animation.clip) {
+            Some(clip) => clip,
+            None => continue,
+        };
+
+        if advance_animation(clip, &mut channel.animation, time.delta_seconds) {
+            animation_finished_events.send(AnimationFinishedEvent {
+                entity,
+                clip: channel.animation.clip.clone(),
+            });
+        }
+        set_sheet(&mut sheet_sprite, clip);
+        sprite_idx += clip.frames[channel.animation.frame].index * channel.stride;
+    }
+    sheet_sprite.index = sprite_idx;
+}
+
+/// Show the entity with the sheet of the clip that it plays
+fn set_sheet(sheet_sprite: &mut SheetSprite, clip: &AnimationClip) {
+    if sheet_sprite.sheet.as_ref() != Some(&clip.sheet) {
+        sheet_sprite.sheet = Some(clip.sheet.clone());
+    }
+}
+
+/// Move the animation forward in time, returns true if the clip has just finished
+pub fn advance_animation(
+    clip: &AnimationClip,
+    animation: &mut Animation,
+    delta_seconds: f32,
+) -> bool {
+    if animation.finished {
+        return false;
+    }
+
+    animation.frame_elapsed += delta_seconds;
+    while animation.frame_elapsed >= clip.frames[animation.frame].duration.as_secs_f32() {
+        animation.frame_elapsed -= clip.frames[animation.frame].duration.as_secs_f32();
+        if !next_frame(clip, animation) {
+            animation.finished = true;
+            animation.frame_elapsed = 0.;
+            return true;
+        }
+    }
+    false
+}
+
+/// Move the animation to the frame that comes after the current one, returns false if the clip
+/// has no more frame to show
+pub fn next_frame(clip: &AnimationClip, animation: &mut Animation) -> bool {
+    let last_frame = clip.frames.len() - 1;
+    match clip.mode {
+        AnimationMode::Once => {
+            if animation.frame == last_frame {
+                return false;
+            }
+            animation.frame += 1;
+        }
+        AnimationMode::Loop => {
+            animation.frame = if animation.frame == last_frame {
+                0
+            } else {
+                animation.frame + 1
+            };
+        }
+        AnimationMode::PingPong => {
+            if last_frame == 0 {
+                return true;
+            }
+            if animation.reversed && animation.frame == 0 {
+                animation.reversed = false;
+            } else if !animation.reversed && animation.frame == last_frame {
+                animation.reversed = true;
+            }
+            if animation.reversed {
+                animation.frame -= 1;
+            } else {
+                animation.frame += 1;
+            }
+        }
     }
+    true
 }
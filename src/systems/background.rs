@@ -0,0 +1,26 @@
+use crate::{
+    components::BackgroundLayer,
+    resource::{Arena, FixedTimestep},
+};
+use bevy::prelude::*;
+
+/// Move the background tiles down, tiles that went past the bottom of the arena are wrapped back
+/// to the top of their layer. The background is only drawn, so it moves with the frames' time
+/// instead of the ticks' and stands still while the game is paused.
+pub fn scroll_background(
+    time: Res<Time>,
+    fixed_timestep: Res<FixedTimestep>,
+    arena: Res<Arena>,
+    background_layer: &BackgroundLayer,
+    mut transform: Mut<Transform>,
+) {
+    if fixed_timestep.paused {
+        return;
+    }
+    *transform.translation.y_mut() -= time.delta_seconds * background_layer.scroll_speed;
+
+    if transform.translation.y() + background_layer.tile_height / 2. <= -arena.height / 2. {
+        *transform.translation.y_mut() +=
+            background_layer.tile_height * background_layer.tile_count as f32;
+    }
+}
@@ -1,32 +1,102 @@
 use crate::{
-    components::{Enemy, HitBox, TimeToLive},
-    constant::{ARENA_HEIGHT, ARENA_WIDTH},
+    components::{ArenaBounds, BoundsPolicy, Explosion, HitBox, Laser, TimeToLive},
+    entity::{release_explosion, release_laser},
+    events::{AnimationFinishedEvent, ReportEvent},
+    resource::{Arena, EntityPools, EventReaders, GameTime},
 };
-use bevy::prelude::*;
+use bevy::{app::AppExit, prelude::*};
 
+/// Remove the entities whose time is up, the lasers go back to their pool
 pub fn despawn_expired_time_to_live(
     mut commands: Commands,
-    time: Res<Time>,
-    entity: Entity,
-    mut time_to_live: Mut<TimeToLive>,
+    time: Res<GameTime>,
+    mut entity_pools: ResMut<EntityPools>,
+    mut query: Query<(Entity, &mut TimeToLive)>,
+    query_laser: Query<&Laser>,
 ) {
-    time_to_live.0.tick(time.delta_seconds);
-    if time_to_live.0.finished {
-        commands.despawn(entity);
+    for (entity, mut time_to_live) in query.iter_mut() {
+        time_to_live.0.tick(time.delta_seconds);
+        if !time_to_live.0.finished {
+            continue;
+        }
+        if query_laser.get(entity).is_ok() {
+            release_laser(&mut commands, &mut entity_pools.lasers, entity);
+        } else {
+            commands.despawn(entity);
+        }
     }
 }
 
-pub fn despawn_out_of_arena_enemy(
+/// Remove the entities that left the arena when their bounds say so, the lasers go back to their
+/// pool. It runs after the collisions, so a fast laser still hits what it went through on its way
+/// out.
+pub fn despawn_out_of_arena(
     mut commands: Commands,
-    entity: Entity,
-    _enemy: &Enemy,
-    HitBox(hit_box): &HitBox,
-    transform: &Transform,
+    arena: Res<Arena>,
+    mut entity_pools: ResMut<EntityPools>,
+    query: Query<(Entity, &ArenaBounds, &HitBox, &Transform)>,
+    query_laser: Query<&Laser>,
 ) {
-    if transform.translation.y() + hit_box.y() / 2. <= -ARENA_HEIGHT / 2.
-        || transform.translation.x() + hit_box.x() / 2. <= -ARENA_WIDTH / 2.
-        || transform.translation.x() - hit_box.x() / 2. >= ARENA_WIDTH / 2.
+    for (entity, bounds, HitBox(hit_box), transform) in query.iter() {
+        let position = Vec2::new(transform.translation.x(), transform.translation.y());
+        if bounds.policy != BoundsPolicy::Despawn
+            || !bounds.is_out(arena.size(), *hit_box, position)
+        {
+            continue;
+        }
+        if query_laser.get(entity).is_ok() {
+            release_laser(&mut commands, &mut entity_pools.lasers, entity);
+        } else {
+            commands.despawn_recursive(entity);
+        }
+    }
+}
+
+/// Put explosions back in their pool as soon as their animation has been played through
+pub fn despawn_finished_explosion(
+    mut commands: Commands,
+    animation_finished_events: Res<Events<AnimationFinishedEvent>>,
+    mut event_readers: ResMut<EventReaders>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_explosion: Query<&Explosion>,
+) {
+    for evt in event_readers
+        .animation_finished
+        .iter(&animation_finished_events)
+    {
+        if query_explosion.get(evt.entity).is_ok() {
+            release_explosion(&mut commands, &mut entity_pools.explosions, evt.entity);
+        }
+    }
+}
+
+/// Report how often the pools had an entity to reuse when the game exits
+pub fn report_pool_metrics(
+    app_exit_events: Res<Events<AppExit>>,
+    entity_pools: Res<EntityPools>,
+    mut event_readers: ResMut<EventReaders>,
+    mut report_events: ResMut<Events<ReportEvent>>,
+) {
+    if event_readers
+        .pool_report
+        .iter(&app_exit_events)
+        .next()
+        .is_none()
+    {
+        return;
+    }
+
+    for (name, pool) in [
+        ("Laser", &entity_pools.lasers),
+        ("Explosion", &entity_pools.explosions),
+    ]
+    .iter()
     {
-        commands.despawn_recursive(entity);
+        report_events.send(ReportEvent::PoolMetrics {
+            pool: name,
+            hits: pool.hits(),
+            misses: pool.misses(),
+            free: pool.free(),
+        });
     }
 }
@@ -1,104 +1,255 @@
 use crate::{
-    components::{Enemy, HitBox, Laser, Ship},
-    constant::ANIMATION_INTERVAL,
+    components::{
+        Collider, CollisionLayer, ContinuousCollision, Enemy, ExplosionKind, Laser, Player,
+        PreviousTranslation,
+    },
+    config::GameConfig,
+    entity::release_laser,
     events::{CollisionLaserEnemyEvent, CollisionLaserShipEvent, SpawnExplosionEvent},
-    resource::EventReaders,
+    resource::{CollisionGrids, EntityPool, EntityPools, EventReaders, GameSettings, Players},
 };
 use bevy::{app::AppExit, prelude::*};
+use std::{cmp::Ordering, collections::HashSet};
 
-pub fn check_laser(
+/// Find the entities that collide and send an event for each kind of collision, the kind is
+/// decided by the layers of the two entities and the pairs of layers without an event are left
+/// alone. The entities are put in a grid for each layer first, so each entity is only tested
+/// against the ones that are close to it. Fast entities are tested along the way they moved during
+/// the tick, so they can not go through thin entities. The events are sent in the order the
+/// entities meet during the tick, so a laser reaches the targets on its way one after the other.
+pub fn check_collisions(
+    mut collision_grids: ResMut<CollisionGrids>,
     mut collision_laser_ship_events: ResMut<Events<CollisionLaserShipEvent>>,
     mut collision_laser_enemy_events: ResMut<Events<CollisionLaserEnemyEvent>>,
-    query_laser: Query<(Entity, &Laser, &HitBox, &Transform)>,
-    query_ship: Query<(Entity, &Ship, &HitBox, &Transform)>,
-    query_enemy: Query<(Entity, &Enemy, &HitBox, &Transform)>,
+    query: Query<(Entity, &CollisionLayer, &Collider, &Transform)>,
+    query_previous_translation: Query<&PreviousTranslation>,
+    query_continuous_collision: Query<&ContinuousCollision>,
 ) {
-    for (laser_entity, laser, HitBox(laser_hit_box), laser_transform) in query_laser.iter() {
-        if query_ship.get(laser.source).is_err() {
-            for (ship_entity, _ship, HitBox(ship_hit_box), ship_transform) in query_ship.iter() {
-                if bevy::sprite::collide_aabb::collide(
-                    laser_transform.translation,
-                    *laser_hit_box,
-                    ship_transform.translation,
-                    *ship_hit_box,
-                )
-                .is_some()
-                {
-                    collision_laser_ship_events.send(CollisionLaserShipEvent {
-                        laser_entity,
-                        ship_entity,
-                    });
-                }
+    // The entities are stored in an order that changes when components are added and removed, so
+    // the bodies are sorted to send the events in the same order after a rollback
+    let mut bodies: Vec<(Entity, CollisionLayer, &Collider, Vec2)> = query
+        .iter()
+        .map(|(entity, layer, collider, transform)| {
+            let position = Vec2::new(transform.translation.x(), transform.translation.y());
+            (entity, *layer, collider, position)
+        })
+        .collect();
+    bodies.sort_by(
+        |(entity, layer, _, position), (other, other_layer, _, other_position)| {
+            position
+                .x()
+                .partial_cmp(&other_position.x())
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    position
+                        .y()
+                        .partial_cmp(&other_position.y())
+                        .unwrap_or(Ordering::Equal)
+                })
+                .then_with(|| layer.memberships.cmp(&other_layer.memberships))
+                .then_with(|| entity.cmp(other))
+        },
+    );
+
+    collision_grids.clear();
+    for (entity, layer, collider, position) in bodies {
+        let previous_position = match query_previous_translation.get(entity) {
+            Ok(previous_translation) => {
+                Vec2::new(previous_translation.0.x(), previous_translation.0.y())
             }
-        }
+            Err(_) => position,
+        };
+        collision_grids.insert(
+            entity,
+            layer,
+            collider,
+            position,
+            position - previous_position,
+            query_continuous_collision.get(entity).is_ok(),
+        );
+    }
+
+    let mut laser_enemy_hits = Vec::new();
+    let mut laser_ship_hits = Vec::new();
+    let mut found = Vec::new();
+    for body_idx in 0..collision_grids.bodies().len() {
+        collision_grids.query(body_idx, &mut found);
+        let body = collision_grids.bodies()[body_idx];
+        for other_idx in found.iter() {
+            if !collision_grids.intersect(body_idx, *other_idx) {
+                continue;
+            }
+            let other = collision_grids.bodies()[*other_idx];
+            let time_of_impact = collision_grids.time_of_impact(body_idx, *other_idx);
 
-        if query_enemy.get(laser.source).is_err() {
-            for (enemy_entity, _enemy, HitBox(enemy_hit_box), enemy_transform) in query_enemy.iter()
-            {
-                if bevy::sprite::collide_aabb::collide(
-                    laser_transform.translation,
-                    *laser_hit_box,
-                    enemy_transform.translation,
-                    *enemy_hit_box,
-                )
-                .is_some()
+            // Either one of the two can be the laser
+            for (first, second) in [(body, other), (other, body)].iter() {
+                if first.layer.is_on(CollisionLayer::PLAYER_PROJECTILE)
+                    && second.layer.is_on(CollisionLayer::ENEMY)
+                {
+                    laser_enemy_hits.push((
+                        time_of_impact,
+                        CollisionLaserEnemyEvent {
+                            laser_entity: first.entity,
+                            enemy_entity: second.entity,
+                        },
+                    ));
+                } else if first.layer.is_on(CollisionLayer::ENEMY_PROJECTILE)
+                    && second.layer.is_on(CollisionLayer::PLAYER)
                 {
-                    collision_laser_enemy_events.send(CollisionLaserEnemyEvent {
-                        laser_entity,
-                        enemy_entity,
-                    });
+                    laser_ship_hits.push((
+                        time_of_impact,
+                        CollisionLaserShipEvent {
+                            laser_entity: first.entity,
+                            ship_entity: second.entity,
+                        },
+                    ));
                 }
             }
         }
     }
+
+    // The sort is stable, so hits at the same time keep the order of the sorted bodies
+    laser_enemy_hits.sort_by(|(time, _), (other_time, _)| {
+        time.partial_cmp(other_time).unwrap_or(Ordering::Equal)
+    });
+    for (_, evt) in laser_enemy_hits {
+        collision_laser_enemy_events.send(evt);
+    }
+    laser_ship_hits.sort_by(|(time, _), (other_time, _)| {
+        time.partial_cmp(other_time).unwrap_or(Ordering::Equal)
+    });
+    for (_, evt) in laser_ship_hits {
+        collision_laser_ship_events.send(evt);
+    }
 }
 
+/// Destroy the ships that are hit and take a life from their players. The players who have lives
+/// left get a new ship after a delay, the game ends once every player is out.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_laser_ship(
     mut commands: Commands,
+    config: Res<GameConfig>,
+    settings: Res<GameSettings>,
     collision_laser_ship_events: Res<Events<CollisionLaserShipEvent>>,
+    mut players: ResMut<Players>,
     mut app_exit_events: ResMut<Events<AppExit>>,
     mut spawn_explosion_events: ResMut<Events<SpawnExplosionEvent>>,
     mut event_readers: ResMut<EventReaders>,
-    query_ship_transform: Query<&Transform>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_laser: Query<&Laser>,
+    query_ship: Query<(&Player, &Transform)>,
 ) {
+    let mut hits = HitResolution::default();
     for evt in event_readers
         .collision_laser_ship
         .iter(&collision_laser_ship_events)
     {
-        let ship_transform = query_ship_transform
-            .get(evt.ship_entity)
-            .expect("Could not get ship transform component");
+        if !hits.is_open(evt.laser_entity, evt.ship_entity) {
+            continue;
+        }
+        let (player, ship_transform) = match (
+            query_laser.get(evt.laser_entity),
+            query_ship.get(evt.ship_entity),
+        ) {
+            (Ok(_), Ok(ship_components)) => ship_components,
+            _ => continue,
+        };
+
+        hits.consume_laser(&mut commands, &mut entity_pools.lasers, evt.laser_entity);
+        if settings.god_mode {
+            continue;
+        }
         spawn_explosion_events.send(SpawnExplosionEvent {
+            explosion_kind: ExplosionKind::Ship,
             explosion_translation: ship_transform.translation,
-            explosion_time_to_live_duration: ANIMATION_INTERVAL * 5,
         });
+        hits.destroy_target(&mut commands, evt.ship_entity);
 
-        commands.despawn_recursive(evt.ship_entity);
-        commands.despawn(evt.laser_entity);
-        app_exit_events.send(AppExit);
+        if let Some(player_state) = players.get_mut(*player) {
+            player_state.lives = player_state.lives.saturating_sub(1);
+            if player_state.lives > 0 {
+                player_state.respawn_timer =
+                    Some(Timer::new(config.ship.respawn_delay_duration(), false));
+            }
+        }
+        if players.all_out() {
+            app_exit_events.send(AppExit);
+        }
     }
 }
 
+/// Destroy the enemies that are hit and credit the players who fired the lasers
+#[allow(clippy::too_many_arguments)]
 pub fn handle_laser_enemy(
     mut commands: Commands,
+    config: Res<GameConfig>,
     collision_laser_enemy_events: Res<Events<CollisionLaserEnemyEvent>>,
+    mut players: ResMut<Players>,
     mut spawn_explosion_events: ResMut<Events<SpawnExplosionEvent>>,
     mut event_readers: ResMut<EventReaders>,
-    query_enemy_transform: Query<&Transform>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_laser: Query<&Laser>,
+    query_enemy: Query<(&Enemy, &Transform)>,
 ) {
+    let mut hits = HitResolution::default();
     for evt in event_readers
         .collision_laser_enemy
         .iter(&collision_laser_enemy_events)
     {
-        let enemy_transform = query_enemy_transform
-            .get(evt.enemy_entity)
-            .expect("Could not get enemy transform component");
+        if !hits.is_open(evt.laser_entity, evt.enemy_entity) {
+            continue;
+        }
+        let (laser, (enemy, enemy_transform)) = match (
+            query_laser.get(evt.laser_entity),
+            query_enemy.get(evt.enemy_entity),
+        ) {
+            (Ok(laser), Ok(enemy_components)) => (laser, enemy_components),
+            _ => continue,
+        };
+
+        hits.consume_laser(&mut commands, &mut entity_pools.lasers, evt.laser_entity);
         spawn_explosion_events.send(SpawnExplosionEvent {
+            explosion_kind: ExplosionKind::Enemy(enemy.variant),
             explosion_translation: enemy_transform.translation,
-            explosion_time_to_live_duration: ANIMATION_INTERVAL * 5,
         });
+        hits.destroy_target(&mut commands, evt.enemy_entity);
+
+        if let Some(player_state) = laser.player.and_then(|player| players.get_mut(player)) {
+            player_state.score += config.enemy.scores.get(enemy.variant);
+        }
+    }
+}
+
+/// The lasers and targets that were used up by the hits of a tick. Collisions are found between
+/// every pair that overlaps, so a laser can touch several targets and a target can be touched by
+/// several lasers in the same tick. The hits come in the order the lasers reach their targets, so
+/// a laser only hits the first target on its way that is still there. A target is only destroyed
+/// once, the lasers that come after it are left for the targets behind it.
+#[derive(Default)]
+struct HitResolution {
+    consumed_lasers: HashSet<Entity>,
+    destroyed_targets: HashSet<Entity>,
+}
+
+impl HitResolution {
+    /// Check if neither the laser nor the target were used up by an earlier hit
+    fn is_open(&self, laser: Entity, target: Entity) -> bool {
+        !self.consumed_lasers.contains(&laser) && !self.destroyed_targets.contains(&target)
+    }
+
+    fn consume_laser(
+        &mut self,
+        commands: &mut Commands,
+        laser_pool: &mut EntityPool,
+        laser: Entity,
+    ) {
+        release_laser(commands, laser_pool, laser);
+        self.consumed_lasers.insert(laser);
+    }
 
-        commands.despawn_recursive(evt.enemy_entity);
-        commands.despawn(evt.laser_entity);
+    fn destroy_target(&mut self, commands: &mut Commands, target: Entity) {
+        commands.despawn_recursive(target);
+        self.destroyed_targets.insert(target);
     }
 }
@@ -0,0 +1,118 @@
+use crate::{
+    components::{CameraShake, Collider, Enemy, EnemySpawner, HitBox, Ship, Weapon},
+    config::GameConfig,
+    entity::spawn_weights,
+    events::{ConfigReloadedEvent, ReportEvent},
+    resource::{AccessibilitySettings, ConfigWatcher, EntityPools, EventReaders},
+};
+use bevy::prelude::*;
+use std::fs;
+
+/// Reload the config when its file has been modified. A file that can not be used is reported and
+/// the game keeps running with the previous config.
+pub fn hot_reload_config(
+    time: Res<Time>,
+    mut config_watcher: ResMut<ConfigWatcher>,
+    mut config: ResMut<GameConfig>,
+    mut config_reloaded_events: ResMut<Events<ConfigReloadedEvent>>,
+    mut report_events: ResMut<Events<ReportEvent>>,
+) {
+    config_watcher.poll_timer.tick(time.delta_seconds);
+    if !config_watcher.poll_timer.finished {
+        return;
+    }
+
+    let modified = fs::metadata(&config_watcher.path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if modified.is_none() || modified == config_watcher.last_modified {
+        return;
+    }
+    config_watcher.last_modified = modified;
+
+    match GameConfig::load(&config_watcher.path) {
+        Ok(new_config) => {
+            *config = new_config;
+            config_reloaded_events.send(ConfigReloadedEvent);
+            report_events.send(ReportEvent::ConfigReloaded(config_watcher.path.clone()));
+        }
+        Err(err) => eprintln!("Could not reload config: {}", err),
+    }
+}
+
+/// Copy the values of a reloaded config into the entities that were created with the old values.
+/// The ships that are already there are resized too, so they can be hit where the new config says.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_reloaded_config(
+    config: Res<GameConfig>,
+    config_reloaded_events: Res<Events<ConfigReloadedEvent>>,
+    mut event_readers: ResMut<EventReaders>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_enemy: Query<&Enemy>,
+    mut query_ship: Query<(&mut Ship, &mut HitBox, &mut Collider)>,
+    mut query_weapon: Query<(&Parent, &mut Weapon, &mut Transform)>,
+    mut query_enemy_spawner: Query<&mut EnemySpawner>,
+    mut query_camera_shake: Query<&mut CameraShake>,
+) {
+    if event_readers
+        .config_reloaded
+        .iter(&config_reloaded_events)
+        .last()
+        .is_none()
+    {
+        return;
+    }
+
+    for (mut ship, mut hit_box, mut collider) in query_ship.iter_mut() {
+        ship.move_speed = config.ship.move_speed;
+        ship.transition_timer.duration = config.ship.state_transition_duration;
+        *hit_box = HitBox(Vec2::new(config.ship.size.0, config.ship.size.1));
+        *collider = Collider::circle(config.ship.core_radius);
+    }
+
+    for (parent, mut weapon, mut transform) in query_weapon.iter_mut() {
+        let laser_config = if query_enemy.get(parent.0).is_ok() {
+            &config.enemy.laser
+        } else {
+            // The ship's weapon is at the tip of the ship
+            transform.translation = config.ship.size.1 * Vec3::unit_y();
+            &config.ship.laser
+        };
+        weapon.cooldown_timer.duration = laser_config.cooldown;
+        weapon.laser_velocity = laser_config.velocity();
+        weapon.laser_hit_box = laser_config.hit_box();
+        weapon.laser_time_to_live_duration = laser_config.time_to_live_duration();
+        weapon.laser_bounds = laser_config.arena_bounds();
+    }
+
+    for mut enemy_spawner in query_enemy_spawner.iter_mut() {
+        enemy_spawner.timer.duration = config.enemy.spawn_interval;
+        enemy_spawner.weights = spawn_weights(&config);
+    }
+
+    for mut camera_shake in query_camera_shake.iter_mut() {
+        camera_shake.trauma_decay = config.camera_shake.trauma_decay;
+        camera_shake.max_offset = config.camera_shake.max_offset;
+        camera_shake.max_roll = config.camera_shake.max_roll;
+        camera_shake.frequency = config.camera_shake.frequency;
+    }
+
+    entity_pools.set_capacities(&config.pools);
+}
+
+/// Take the accessibility options of a reloaded config, so players can try them while they play
+pub fn apply_reloaded_accessibility(
+    config: Res<GameConfig>,
+    config_reloaded_events: Res<Events<ConfigReloadedEvent>>,
+    mut event_readers: ResMut<EventReaders>,
+    mut accessibility_settings: ResMut<AccessibilitySettings>,
+) {
+    if event_readers
+        .accessibility_reloaded
+        .iter(&config_reloaded_events)
+        .last()
+        .is_some()
+    {
+        *accessibility_settings = AccessibilitySettings::new(&config.accessibility);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    components::{CameraShake, ExplosionKind},
+    config::GameConfig,
+    events::SpawnExplosionEvent,
+    resource::{AccessibilitySettings, EventReaders, FixedTimestep, GameTime, HitStop},
+};
+use bevy::prelude::*;
+
+/// Advance the game world's time by one tick, the game world does not move while in hit-stop
+pub fn advance_game_time(
+    fixed_timestep: Res<FixedTimestep>,
+    mut hit_stop: ResMut<HitStop>,
+    mut game_time: ResMut<GameTime>,
+) {
+    if hit_stop.remaining_seconds > 0. {
+        hit_stop.remaining_seconds =
+            (hit_stop.remaining_seconds - fixed_timestep.step_seconds).max(0.);
+        game_time.delta_seconds = 0.;
+    } else {
+        game_time.delta_seconds = fixed_timestep.step_seconds;
+    }
+}
+
+/// Add trauma to the cameras and start a hit-stop based on what exploded
+pub fn explosion_impact(
+    config: Res<GameConfig>,
+    spawn_explosion_events: Res<Events<SpawnExplosionEvent>>,
+    mut event_readers: ResMut<EventReaders>,
+    mut hit_stop: ResMut<HitStop>,
+    mut query_camera_shake: Query<&mut CameraShake>,
+) {
+    for evt in event_readers
+        .explosion_feedback
+        .iter(&spawn_explosion_events)
+    {
+        let (trauma, hit_stop_seconds) = match evt.explosion_kind {
+            ExplosionKind::Enemy(variant) => (
+                *config.camera_shake.trauma_enemy.get(variant),
+                *config.hit_stop.enemy.get(variant),
+            ),
+            ExplosionKind::Ship => (config.camera_shake.trauma_ship, config.hit_stop.ship),
+        };
+
+        for mut camera_shake in query_camera_shake.iter_mut() {
+            camera_shake.trauma = (camera_shake.trauma + trauma).min(1.);
+        }
+        hit_stop.remaining_seconds = hit_stop.remaining_seconds.max(hit_stop_seconds);
+    }
+}
+
+/// Offset and roll the camera based on its trauma, then let the trauma decay. Only the view is
+/// moved, the arena and everything that is simulated inside of it stays where it is.
+pub fn camera_shake(
+    time: Res<Time>,
+    accessibility_settings: Res<AccessibilitySettings>,
+    mut camera_shake: Mut<CameraShake>,
+    mut transform: Mut<Transform>,
+) {
+    let shake = if accessibility_settings.camera_shake {
+        camera_shake.trauma * camera_shake.trauma
+    } else {
+        0.
+    };
+
+    let t = time.seconds_since_startup as f32 * camera_shake.frequency;
+    *transform.translation.x_mut() = camera_shake.max_offset * shake * shake_noise(t, 0.);
+    *transform.translation.y_mut() = camera_shake.max_offset * shake * shake_noise(t, 1.);
+    transform.rotation = Quat::from_rotation_z(camera_shake.max_roll * shake * shake_noise(t, 2.));
+
+    camera_shake.trauma =
+        (camera_shake.trauma - camera_shake.trauma_decay * time.delta_seconds).max(0.);
+}
+
+/// Smooth value in the range [-1, 1] that changes over time, different seeds give different
+/// curves
+fn shake_noise(t: f32, seed: f32) -> f32 {
+    ((t + seed * 17.).sin() + (t * 1.7 + seed * 31.).sin()) / 2.
+}
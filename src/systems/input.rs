@@ -0,0 +1,415 @@
+use crate::{
+    components::{Player, RebindingLine, Ship},
+    constant::{
+        GAMEPAD_STICK_DEADZONE, MAX_PLAYERS, POINTER_FOLLOW_DURATION, REBIND_CONFIRM_KEY,
+        REBIND_KEY,
+    },
+    events::ReportEvent,
+    input::{
+        gamepad_just_pressed, gamepad_pressed, gamepad_stick, key_name, Action, InputBindings,
+    },
+    resource::{
+        ArenaView, ConnectedGamepads, EventReaders, FixedTimestep, GameSettings, InputSource,
+        PlayerInput, PlayerInputs, Players, Pointer, Rebinding,
+    },
+};
+use bevy::{
+    app::AppExit,
+    input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadEventType},
+        keyboard::KeyCode,
+        mouse::MouseButton,
+        Axis, Input,
+    },
+    prelude::*,
+    window::CursorMoved,
+};
+
+/// Set the players' input of the current tick, either from the devices, from the replay that is
+/// being played back or from the network session. Scripted input is left as it is. The game exits
+/// once the replay runs out of input.
+#[allow(clippy::too_many_arguments)]
+pub fn read_player_inputs(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    connected_gamepads: Res<ConnectedGamepads>,
+    pointer: Res<Pointer>,
+    input_bindings: Res<InputBindings>,
+    mut input_source: ResMut<InputSource>,
+    mut player_inputs: ResMut<PlayerInputs>,
+    mut app_exit_events: ResMut<Events<AppExit>>,
+) {
+    let devices_player_input = |player: Player| {
+        let mut player_input = keyboard_player_input(&keyboard_input, &input_bindings, player);
+        if let Some(gamepad) = connected_gamepads.gamepad(player) {
+            for (action, bit) in ACTION_BITS.iter() {
+                if gamepad_pressed(&gamepad_input, gamepad, *action) {
+                    player_input.set(*bit, true);
+                }
+            }
+            player_input.set_stick(gamepad_stick(
+                &gamepad_axes,
+                gamepad,
+                GAMEPAD_STICK_DEADZONE,
+            ));
+        }
+        // Only the first player can be controlled with the pointer
+        if player == Player(0) {
+            if pointer.tilt != Vec2::zero() {
+                player_input.set_stick(pointer.tilt);
+            }
+            if pointer.fire {
+                player_input.set(PlayerInput::FIRE, true);
+            }
+            if pointer.bomb {
+                player_input.set(PlayerInput::BOMB, true);
+            }
+        }
+        player_input
+    };
+    let devices_player_inputs = || {
+        let mut player_inputs = PlayerInputs::default();
+        for (player_idx, player_input) in player_inputs.0.iter_mut().enumerate() {
+            *player_input = devices_player_input(Player(player_idx));
+        }
+        player_inputs
+    };
+
+    match &mut *input_source {
+        InputSource::Devices => *player_inputs = devices_player_inputs(),
+        InputSource::Recording { replay, .. } => {
+            *player_inputs = devices_player_inputs();
+            replay.inputs.push(*player_inputs);
+        }
+        InputSource::Scripted => {}
+        // The devices always control the first player's keys, gamepad and pointer, the session
+        // hands their input to whichever player is local
+        InputSource::Network(session) => {
+            *player_inputs = session.inputs(|| devices_player_input(Player(0)))
+        }
+        InputSource::Playback { replay, tick } => match replay.inputs.get(*tick) {
+            Some(inputs) => {
+                *player_inputs = *inputs;
+                *tick += 1;
+            }
+            None => {
+                *player_inputs = PlayerInputs::default();
+                app_exit_events.send(AppExit);
+            }
+        },
+    }
+}
+
+/// Move the pointer with the mouse cursor, from the window's pixels to the arena's units
+pub fn track_pointer(
+    arena_view: Res<ArenaView>,
+    windows: Res<Windows>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    mut event_readers: ResMut<EventReaders>,
+    mut pointer: ResMut<Pointer>,
+) {
+    for event in event_readers.cursor_moved.iter(&cursor_moved_events) {
+        if let Some(window) = windows.get(event.id) {
+            let window_center = Vec2::new(window.width() as f32, window.height() as f32) / 2.;
+            pointer.position = Some((event.position - window_center) / arena_view.scale);
+        }
+    }
+}
+
+/// Tilt the pointer's stick toward the cursor when the local player's ship is controlled with the
+/// pointer. The ship closes most of its distance to the cursor in `POINTER_FOLLOW_DURATION`, so
+/// it slows down as it gets close instead of shaking around the cursor. The left mouse button
+/// fires and the right one bombs.
+pub fn follow_pointer(
+    settings: Res<GameSettings>,
+    input_source: Res<InputSource>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut pointer: ResMut<Pointer>,
+    query: Query<(&Ship, &Player, &Transform)>,
+) {
+    if !settings.pointer_control {
+        return;
+    }
+
+    pointer.fire = mouse_input.pressed(MouseButton::Left);
+    pointer.bomb = mouse_input.pressed(MouseButton::Right);
+    pointer.tilt = Vec2::zero();
+    let local_player = match &*input_source {
+        InputSource::Network(session) => session.local_player(),
+        _ => Player(0),
+    };
+    if let Some(position) = pointer.position {
+        for (ship, _, transform) in query
+            .iter()
+            .filter(|(_, player, _)| **player == local_player)
+        {
+            let ship_position = Vec2::new(transform.translation.x(), transform.translation.y());
+            pointer.tilt = (position - ship_position) / (ship.move_speed * POINTER_FOLLOW_DURATION);
+        }
+    }
+}
+
+/// Save the recorded replay when the game exits
+pub fn save_replay_on_exit(
+    app_exit_events: Res<Events<AppExit>>,
+    input_source: Res<InputSource>,
+    mut event_readers: ResMut<EventReaders>,
+) {
+    if event_readers
+        .app_exit
+        .iter(&app_exit_events)
+        .next()
+        .is_none()
+    {
+        return;
+    }
+
+    if let InputSource::Recording { path, replay } = &*input_source {
+        match replay.save(path) {
+            Ok(()) => println!("Saved replay to {}", path.display()),
+            Err(err) => eprintln!("Could not save replay: {}", err),
+        }
+    }
+}
+
+/// Keep track of the gamepads that are plugged in. The game is paused when the gamepad of a player
+/// who has joined is unplugged, so their ship is not left without a player. The player gets their
+/// control back once a gamepad is plugged in again.
+pub fn connect_gamepads(
+    players: Res<Players>,
+    gamepad_events: Res<Events<GamepadEvent>>,
+    mut event_readers: ResMut<EventReaders>,
+    mut connected_gamepads: ResMut<ConnectedGamepads>,
+    mut fixed_timestep: ResMut<FixedTimestep>,
+    mut report_events: ResMut<Events<ReportEvent>>,
+) {
+    for GamepadEvent(gamepad, event_type) in event_readers.gamepad.iter(&gamepad_events) {
+        match event_type {
+            GamepadEventType::Connected => {
+                if let Some(player) = connected_gamepads.connect(*gamepad) {
+                    report_events.send(ReportEvent::GamepadConnected {
+                        gamepad: *gamepad,
+                        player,
+                    });
+                }
+            }
+            GamepadEventType::Disconnected => {
+                let player = match connected_gamepads.disconnect(*gamepad) {
+                    Some(player) => player,
+                    None => continue,
+                };
+                report_events.send(ReportEvent::GamepadDisconnected(*gamepad));
+
+                let had_player = players.0.get(player.0).map_or(false, Option::is_some);
+                if had_player && !fixed_timestep.paused {
+                    fixed_timestep.paused = true;
+                    report_events.send(ReportEvent::Paused);
+                }
+            }
+        }
+    }
+}
+
+/// Pause or resume the game
+pub fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    connected_gamepads: Res<ConnectedGamepads>,
+    input_bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    mut fixed_timestep: ResMut<FixedTimestep>,
+    mut report_events: ResMut<Events<ReportEvent>>,
+) {
+    if let Rebinding::Active { .. } = *rebinding {
+        return;
+    }
+    let pause_pressed = (0..MAX_PLAYERS).map(Player).any(|player| {
+        let gamepad_pause = connected_gamepads.gamepad(player).map_or(false, |gamepad| {
+            gamepad_just_pressed(&gamepad_input, gamepad, Action::Pause)
+        });
+        gamepad_pause || input_bindings.just_pressed(&keyboard_input, player, Action::Pause)
+    });
+    if pause_pressed {
+        fixed_timestep.paused = !fixed_timestep.paused;
+        report_events.send(if fixed_timestep.paused {
+            ReportEvent::Paused
+        } else {
+            ReportEvent::Resumed
+        });
+    }
+}
+
+/// Let the players choose new keys for every action while the game is paused. The actions are
+/// shown one after the other, the first player's then the second player's. The keys that are
+/// pressed are bound to the action once the confirm key is pressed. Confirming without pressing a
+/// key keeps the current keys. A key that is bound to another action has to be pressed twice to be
+/// moved over to the current action. What happened to the last key is kept as a message for the
+/// rebinding screen.
+pub fn rebind_actions(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut input_bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut fixed_timestep: ResMut<FixedTimestep>,
+) {
+    if let Rebinding::Inactive = *rebinding {
+        if keyboard_input.just_pressed(REBIND_KEY) {
+            *rebinding = Rebinding::Active {
+                player: Player(0),
+                action_idx: 0,
+                keys: Vec::new(),
+                conflicting_key: None,
+                bindings: input_bindings.clone(),
+                was_paused: fixed_timestep.paused,
+                message: String::new(),
+            };
+            fixed_timestep.paused = true;
+        }
+        return;
+    }
+
+    let (player, action_idx, keys, conflicting_key, bindings, was_paused, message) =
+        match &mut *rebinding {
+            Rebinding::Active {
+                player,
+                action_idx,
+                keys,
+                conflicting_key,
+                bindings,
+                was_paused,
+                message,
+            } => (
+                player,
+                action_idx,
+                keys,
+                conflicting_key,
+                bindings,
+                *was_paused,
+                message,
+            ),
+            Rebinding::Inactive => return,
+        };
+
+    let action = Action::ALL[*action_idx];
+    for &key in keyboard_input.get_just_pressed() {
+        if key == REBIND_KEY {
+            continue;
+        }
+        if key == REBIND_CONFIRM_KEY {
+            if !keys.is_empty() {
+                bindings.set_keys(*player, action, keys.drain(..).collect());
+            }
+            *conflicting_key = None;
+            message.clear();
+            *action_idx += 1;
+            if *action_idx == Action::ALL.len() {
+                *action_idx = 0;
+                player.0 += 1;
+            }
+            break;
+        }
+
+        let name = match key_name(key) {
+            Some(name) => name,
+            None => {
+                *message = format!("{:?} can not be bound", key);
+                continue;
+            }
+        };
+        if keys.contains(&key) {
+            continue;
+        }
+        match bindings.action(key) {
+            Some((other_player, other_action))
+                if (other_player, other_action) != (*player, action)
+                    && *conflicting_key != Some(key) =>
+            {
+                *message = format!(
+                    "{} is bound to {} of player {}, press it again to bind it to {} instead",
+                    name,
+                    other_action,
+                    other_player.0 + 1,
+                    action
+                );
+                *conflicting_key = Some(key);
+            }
+            Some((other_player, other_action))
+                if (other_player, other_action) != (*player, action) =>
+            {
+                let other_keys = bindings
+                    .keys(other_player, other_action)
+                    .iter()
+                    .copied()
+                    .filter(|other_key| *other_key != key)
+                    .collect();
+                bindings.set_keys(other_player, other_action, other_keys);
+                *conflicting_key = None;
+                keys.push(key);
+                *message = format!(
+                    "{} moved from {} of player {} to {}",
+                    name,
+                    other_action,
+                    other_player.0 + 1,
+                    action
+                );
+            }
+            _ => {
+                *conflicting_key = None;
+                keys.push(key);
+                *message = format!("{} added to {}", name, action);
+            }
+        }
+    }
+
+    if player.0 < MAX_PLAYERS {
+        return;
+    }
+
+    *input_bindings = bindings.clone();
+    match input_bindings.save(&settings.bindings_path) {
+        Ok(()) => println!("Saved the bindings to {}", settings.bindings_path.display()),
+        Err(err) => eprintln!("Could not save the bindings: {}", err),
+    }
+    fixed_timestep.paused = was_paused;
+    *rebinding = Rebinding::Inactive;
+}
+
+/// Show the rebinding screen's lines, the text is only touched when it changes
+pub fn show_rebinding_screen(
+    rebinding: ChangedRes<Rebinding>,
+    mut query: Query<(&RebindingLine, &mut Text)>,
+) {
+    let lines = rebinding.screen_lines();
+    for (line, mut text) in query.iter_mut() {
+        if text.value != lines[line.0] {
+            text.value = lines[line.0].clone();
+        }
+    }
+}
+
+/// The bit of the player's input that is set by each action
+const ACTION_BITS: [(Action, u8); 7] = [
+    (Action::MoveLeft, PlayerInput::LEFT),
+    (Action::MoveRight, PlayerInput::RIGHT),
+    (Action::MoveUp, PlayerInput::UP),
+    (Action::MoveDown, PlayerInput::DOWN),
+    (Action::Fire, PlayerInput::FIRE),
+    (Action::Bomb, PlayerInput::BOMB),
+    (Action::Focus, PlayerInput::FOCUS),
+];
+
+fn keyboard_player_input(
+    keyboard_input: &Input<KeyCode>,
+    input_bindings: &InputBindings,
+    player: Player,
+) -> PlayerInput {
+    let mut player_input = PlayerInput::default();
+    for (action, bit) in ACTION_BITS.iter() {
+        player_input.set(
+            *bit,
+            input_bindings.pressed(keyboard_input, player, *action),
+        );
+    }
+    player_input
+}
@@ -1,7 +1,16 @@
 pub mod animation;
+pub mod background;
 pub mod cleanup;
 pub mod collide;
+#[cfg(debug_assertions)]
+pub mod config;
+pub mod feedback;
+pub mod input;
 pub mod motion;
+pub mod player;
 pub mod ship;
+pub mod simulation;
 pub mod spawner;
+pub mod sprite;
 pub mod weapon;
+pub mod window;
@@ -1,6 +1,6 @@
 use crate::{
-    components::{ConstrainedToArena, HitBox, Velocity},
-    constant::{ARENA_HEIGHT, ARENA_WIDTH},
+    components::{ArenaBounds, BoundsPolicy, HitBox, PreviousTranslation, Velocity},
+    resource::{Arena, GameTime},
 };
 use bevy::prelude::*;
 
@@ -9,7 +9,7 @@ use bevy::prelude::*;
 /// Change entities' position based on its current velocity
 pub fn apply_velocity(
     // Resources
-    time: Res<Time>,
+    time: Res<GameTime>,
     Velocity(velocity): &Velocity,
     mut transform: Mut<Transform>,
 ) {
@@ -17,24 +17,72 @@ pub fn apply_velocity(
     *transform.translation.y_mut() += time.delta_seconds * velocity.y();
 }
 
-pub fn constrained_to_arena(
-    _constrained_to_arena: &ConstrainedToArena,
-    HitBox(hit_box): &HitBox,
-    mut transform: Mut<Transform>,
+/// Keep the entities in the arena the way their bounds say, the ones that are removed once they
+/// are out are left to the cleanup. Entities that wrap around keep moving from where they were, so
+/// they are not swept across the whole arena.
+pub fn apply_arena_bounds(
+    arena: Res<Arena>,
+    mut query: Query<(Entity, &ArenaBounds, &HitBox, &mut Transform)>,
+    mut query_velocity: Query<&mut Velocity>,
+    mut query_previous_translation: Query<&mut PreviousTranslation>,
 ) {
-    // X-axis movement
-    let max_offset_x_from_center = (ARENA_WIDTH - hit_box.x()) / 2.;
-    *transform.translation.x_mut() = transform
-        .translation
-        .x()
-        .min(max_offset_x_from_center)
-        .max(-max_offset_x_from_center);
+    for (entity, bounds, HitBox(hit_box), mut transform) in query.iter_mut() {
+        let (max_inside, min_outside) = bounds.limits(arena.size(), *hit_box);
+        let position = Vec2::new(transform.translation.x(), transform.translation.y());
+
+        let new_position = match bounds.policy {
+            BoundsPolicy::Clamp => position.min(max_inside).max(-max_inside),
+            BoundsPolicy::Despawn => continue,
+            BoundsPolicy::Wrap => {
+                let new_position = Vec2::new(
+                    wrap_axis(position.x(), min_outside.x()),
+                    wrap_axis(position.y(), min_outside.y()),
+                );
+                if let Ok(mut previous_translation) = query_previous_translation.get_mut(entity) {
+                    let shift = new_position - position;
+                    *previous_translation.0.x_mut() += shift.x();
+                    *previous_translation.0.y_mut() += shift.y();
+                }
+                new_position
+            }
+            BoundsPolicy::Bounce => {
+                let (x, turn_x) = bounce_axis(position.x(), max_inside.x());
+                let (y, turn_y) = bounce_axis(position.y(), max_inside.y());
+                if let Ok(mut velocity) = query_velocity.get_mut(entity) {
+                    if let Some(sign) = turn_x {
+                        *velocity.0.x_mut() = sign * velocity.0.x().abs();
+                    }
+                    if let Some(sign) = turn_y {
+                        *velocity.0.y_mut() = sign * velocity.0.y().abs();
+                    }
+                }
+                Vec2::new(x, y)
+            }
+        };
+        *transform.translation.x_mut() = new_position.x();
+        *transform.translation.y_mut() = new_position.y();
+    }
+}
+
+/// Move a position that is out past one edge to right outside of the opposite edge
+fn wrap_axis(position: f32, min_outside: f32) -> f32 {
+    if position >= min_outside {
+        position - 2. * min_outside
+    } else if position <= -min_outside {
+        position + 2. * min_outside
+    } else {
+        position
+    }
+}
 
-    // Y-axis movement
-    let max_offset_y_from_center = (ARENA_HEIGHT - hit_box.y()) / 2.;
-    *transform.translation.y_mut() = transform
-        .translation
-        .y()
-        .min(max_offset_y_from_center)
-        .max(-max_offset_y_from_center);
+/// Mirror a position that went past an edge back into the arena, with the sign that the velocity
+/// on the axis gets when it has to turn back
+fn bounce_axis(position: f32, max_inside: f32) -> (f32, Option<f32>) {
+    if position > max_inside {
+        (2. * max_inside - position, Some(-1.))
+    } else if position < -max_inside {
+        (-2. * max_inside - position, Some(1.))
+    } else {
+        (position, None)
+    }
 }
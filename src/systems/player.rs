@@ -0,0 +1,85 @@
+use crate::{
+    animation::LayeredAnimations,
+    components::Player,
+    config::GameConfig,
+    entity::spawn_ship,
+    events::ReportEvent,
+    resource::{AnnouncedPlayers, GameTime, PlayerInput, PlayerInputs, PlayerState, Players},
+};
+use bevy::prelude::*;
+
+/// Let the players who are not in the run yet join it by pressing fire
+pub fn join_players(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    layered_animations: Res<LayeredAnimations>,
+    player_inputs: Res<PlayerInputs>,
+    mut players: ResMut<Players>,
+) {
+    for (player_idx, player_state) in players.0.iter_mut().enumerate() {
+        let player = Player(player_idx);
+        if player_state.is_none() && player_inputs.get(player).pressed(PlayerInput::FIRE) {
+            *player_state = Some(PlayerState::new(config.ship.lives, config.ship.bombs));
+            spawn_ship(&mut commands, &layered_animations, &config, player);
+        }
+    }
+}
+
+/// Give a new ship to the players who lost one, once their respawn delay is over. The new ship
+/// comes with a full set of bombs.
+pub fn respawn_ships(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    config: Res<GameConfig>,
+    layered_animations: Res<LayeredAnimations>,
+    mut players: ResMut<Players>,
+) {
+    for (player_idx, player_state) in players.0.iter_mut().enumerate() {
+        let player_state = match player_state {
+            Some(player_state) => player_state,
+            None => continue,
+        };
+        let respawned = match &mut player_state.respawn_timer {
+            Some(respawn_timer) => {
+                respawn_timer.tick(time.delta_seconds);
+                respawn_timer.finished
+            }
+            None => false,
+        };
+        if respawned {
+            player_state.respawn_timer = None;
+            player_state.bombs = config.ship.bombs;
+            spawn_ship(
+                &mut commands,
+                &layered_animations,
+                &config,
+                Player(player_idx),
+            );
+        }
+    }
+}
+
+/// Report when players join the run and when they are out of it, by comparing the players with
+/// how they were when this last ran
+pub fn announce_players(
+    players: Res<Players>,
+    mut announced_players: ResMut<AnnouncedPlayers>,
+    mut report_events: ResMut<Events<ReportEvent>>,
+) {
+    if let Some(announced) = &announced_players.0 {
+        for (player_idx, states) in announced.0.iter().zip(players.0.iter()).enumerate() {
+            let player = Player(player_idx);
+            match states {
+                (None, Some(_)) => report_events.send(ReportEvent::PlayerJoined(player)),
+                (Some(before), Some(after)) if before.lives > 0 && after.lives == 0 => {
+                    report_events.send(ReportEvent::PlayerOut {
+                        player,
+                        score: after.score,
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    announced_players.0 = Some(players.clone());
+}
@@ -1,49 +1,75 @@
-use crate::components::{Ship, ShipAnimationState, Velocity};
-use bevy::{
-    input::{keyboard::KeyCode, Input},
-    prelude::*,
+use crate::{
+    animation::LayeredAnimations,
+    components::{LayeredAnimation, Player, Ship, ShipAnimationState, Velocity},
+    config::GameConfig,
+    constant::LAYERED_ANIMATION_SHIP,
+    resource::{GameTime, PlayerInput, PlayerInputs},
 };
+use bevy::prelude::*;
 
-/// Change ship's directions based on user's keyboard input
-pub fn keyboard_control(
-    keyboard_input: Res<Input<KeyCode>>,
+/// Change ship's directions based on its player's input, the ship moves slower while focusing
+pub fn player_control(
+    config: Res<GameConfig>,
+    player_inputs: Res<PlayerInputs>,
     ship: &Ship,
+    player: &Player,
     mut velocity: Mut<Velocity>,
 ) {
+    let player_input = player_inputs.get(*player);
+    let move_speed = if player_input.pressed(PlayerInput::FOCUS) {
+        ship.move_speed * config.ship.focus_speed_factor
+    } else {
+        ship.move_speed
+    };
+
+    // The tilt of the stick sets the ship's speed, the directions always move it at full speed
+    let stick = player_input.stick();
+    if stick != Vec2::zero() {
+        let stick = if stick.length() > 1. {
+            stick.normalize()
+        } else {
+            stick
+        };
+        *velocity.0.x_mut() = move_speed * stick.x();
+        *velocity.0.y_mut() = move_speed * stick.y();
+        return;
+    }
+
     let mut x_direction = 0.;
-    if keyboard_input.pressed(KeyCode::Left) {
+    if player_input.pressed(PlayerInput::LEFT) {
         x_direction -= 1.;
     }
-    if keyboard_input.pressed(KeyCode::Right) {
+    if player_input.pressed(PlayerInput::RIGHT) {
         x_direction += 1.;
     }
 
     let mut y_direction = 0.;
-    if keyboard_input.pressed(KeyCode::Up) {
+    if player_input.pressed(PlayerInput::UP) {
         y_direction += 1.;
     }
-    if keyboard_input.pressed(KeyCode::Down) {
+    if player_input.pressed(PlayerInput::DOWN) {
         y_direction -= 1.;
     }
 
     // Ensure ship speed is capped at `max_speed` when moving diagonally
     if x_direction != 0. && y_direction != 0. {
-        *velocity.0.y_mut() = (ship.move_speed / f32::sqrt(2.)) * y_direction;
-        *velocity.0.x_mut() = (ship.move_speed / f32::sqrt(2.)) * x_direction;
+        *velocity.0.y_mut() = (move_speed / f32::sqrt(2.)) * y_direction;
+        *velocity.0.x_mut() = (move_speed / f32::sqrt(2.)) * x_direction;
     } else {
-        *velocity.0.y_mut() = ship.move_speed * y_direction;
-        *velocity.0.x_mut() = ship.move_speed * x_direction;
+        *velocity.0.y_mut() = move_speed * y_direction;
+        *velocity.0.x_mut() = move_speed * x_direction;
     }
 }
 
-/// Change the ship's animation state and change the current index to the index of the sprite
-/// that represents that state. The ship has to be in the new state for at least some set amount
-/// of duration before being able to change its state again
+/// Change the ship's animation state and play the clips that the ship's animation gives to that
+/// state on its channels. The ship has to be in the new state for at least some set amount of
+/// duration before being able to change its state again
 pub fn animation_state_transition(
-    time: Res<Time>,
+    time: Res<GameTime>,
+    layered_animations: Res<LayeredAnimations>,
     velocity: &Velocity,
     mut ship: Mut<Ship>,
-    mut sprite: Mut<TextureAtlasSprite>,
+    mut layered_animation: Mut<LayeredAnimation>,
 ) {
     ship.transition_timer.tick(time.delta_seconds);
     if ship.transition_timer.finished {
@@ -80,13 +106,12 @@ pub fn animation_state_transition(
         if new_animation_state != ship.animation_state {
             ship.transition_timer.reset();
             ship.animation_state = new_animation_state;
-            sprite.index = match ship.animation_state {
-                ShipAnimationState::FullLeft => 0,
-                ShipAnimationState::HalfLeft => 1,
-                ShipAnimationState::Stabilized => 2,
-                ShipAnimationState::HalfRight => 3,
-                ShipAnimationState::FullRight => 4,
-            };
+            let ship_animation = layered_animations
+                .get(LAYERED_ANIMATION_SHIP)
+                .expect("Could not get ship animation");
+            for (channel, clip) in ship_animation.state_clips(ship.animation_state) {
+                layered_animation.play(channel, clip);
+            }
         }
     }
 }
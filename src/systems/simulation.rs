@@ -0,0 +1,185 @@
+use crate::{
+    components::PreviousTranslation,
+    net::NetSession,
+    resource::{FixedTimestep, InputSource, Players},
+    snapshot::Snapshot,
+};
+use bevy::{app::AppExit, ecs::System, prelude::*};
+use std::collections::VecDeque;
+
+/// Systems that advance the game world by one tick. They are run one after the other in the order
+/// they were added, and the commands of a system are applied before the next one runs, so a tick
+/// gives the same result no matter how the frames are timed.
+#[derive(Default)]
+pub struct FixedSchedule {
+    systems: Vec<Box<dyn System>>,
+    initialized: bool,
+}
+
+impl FixedSchedule {
+    pub fn add_system(&mut self, system: Box<dyn System>) -> &mut Self {
+        self.systems.push(system);
+        self
+    }
+
+    /// Run every system once
+    pub fn run_tick(&mut self, world: &mut World, resources: &mut Resources) {
+        if !self.initialized {
+            for system in self.systems.iter_mut() {
+                system.initialize(world, resources);
+            }
+            self.initialized = true;
+        }
+
+        for system in self.systems.iter_mut() {
+            system.update(world);
+            system.run(world, resources);
+            system.run_thread_local(world, resources);
+        }
+    }
+}
+
+/// Create a thread local system that runs as many ticks of the schedule as the frame's time
+/// allows
+pub fn run_fixed_timestep(
+    mut schedule: FixedSchedule,
+) -> impl FnMut(&mut World, &mut Resources) + Send + Sync + 'static {
+    move |world, resources| {
+        let steps = {
+            let time = resources.get::<Time>().expect("Could not get time");
+            let mut fixed_timestep = resources
+                .get_mut::<FixedTimestep>()
+                .expect("Could not get fixed timestep");
+            fixed_timestep.accumulate(time.delta_seconds)
+        };
+
+        for _ in 0..steps {
+            schedule.run_tick(world, resources);
+        }
+    }
+}
+
+/// Create a thread local system that runs the ticks of a game that is played over the network. The
+/// ticks are run with a guess of the other player's input, when a guess turns out to be wrong the
+/// game world is put back to how it was before that tick and the ticks from there are run again.
+pub fn run_rollback_timestep(
+    mut schedule: FixedSchedule,
+) -> impl FnMut(&mut World, &mut Resources) + Send + Sync + 'static {
+    // The game world before each of the ticks that have not been confirmed yet
+    let mut snapshots: VecDeque<(usize, Snapshot)> = VecDeque::new();
+    move |world, resources| {
+        let steps = {
+            let time = resources.get::<Time>().expect("Could not get time");
+            let mut fixed_timestep = resources
+                .get_mut::<FixedTimestep>()
+                .expect("Could not get fixed timestep");
+            fixed_timestep.accumulate(time.delta_seconds)
+        };
+
+        // A tick that was run with a wrong guess could end the game, so the ticks can not exit
+        // the game on their own
+        let saved_app_exit_events = std::mem::take(
+            &mut *resources
+                .get_mut::<Events<AppExit>>()
+                .expect("Could not get app exit events"),
+        );
+
+        let (mispredicted_tick, current_tick) = with_net_session(resources, |session| {
+            session.poll();
+            (session.take_mispredicted_tick(), session.tick())
+        });
+        if let Some(mispredicted_tick) = mispredicted_tick {
+            let first_wrong = snapshots
+                .iter()
+                .position(|(tick, _)| *tick == mispredicted_tick)
+                .expect("Could not find snapshot of mispredicted tick");
+            snapshots[first_wrong].1.restore(world, resources);
+            snapshots.truncate(first_wrong);
+            with_net_session(resources, |session| session.rewind(mispredicted_tick));
+            for _ in mispredicted_tick..current_tick {
+                run_net_tick(&mut schedule, &mut snapshots, world, resources);
+            }
+        }
+        for _ in 0..steps {
+            if !with_net_session(resources, |session| session.can_advance()) {
+                break;
+            }
+            run_net_tick(&mut schedule, &mut snapshots, world, resources);
+        }
+
+        let (confirmed_ticks, disconnected) = with_net_session(resources, |session| {
+            session.send_inputs();
+            (session.confirmed_ticks(), session.is_disconnected())
+        });
+        while snapshots
+            .front()
+            .map_or(false, |(tick, _)| *tick < confirmed_ticks)
+        {
+            snapshots.pop_front();
+        }
+
+        // The game ends once the confirmed ticks have put every player out
+        let all_out = match snapshots.front() {
+            Some((tick, snapshot)) if *tick == confirmed_ticks => snapshot.players().all_out(),
+            _ => resources
+                .get::<Players>()
+                .expect("Could not get players")
+                .all_out(),
+        };
+        let mut app_exit_events = resources
+            .get_mut::<Events<AppExit>>()
+            .expect("Could not get app exit events");
+        *app_exit_events = saved_app_exit_events;
+        if all_out {
+            app_exit_events.send(AppExit);
+        } else if disconnected {
+            eprintln!("The other player left the game");
+            app_exit_events.send(AppExit);
+        }
+    }
+}
+
+/// Run the session's current tick, the game world is saved before it so it can be rolled back
+fn run_net_tick(
+    schedule: &mut FixedSchedule,
+    snapshots: &mut VecDeque<(usize, Snapshot)>,
+    world: &mut World,
+    resources: &mut Resources,
+) {
+    let tick = with_net_session(resources, |session| session.tick());
+    snapshots.push_back((tick, Snapshot::take(world, resources)));
+    schedule.run_tick(world, resources);
+    with_net_session(resources, NetSession::advance);
+}
+
+fn with_net_session<T>(resources: &Resources, f: impl FnOnce(&mut NetSession) -> T) -> T {
+    let mut input_source = resources
+        .get_mut::<InputSource>()
+        .expect("Could not get input source");
+    match &mut *input_source {
+        InputSource::Network(session) => f(session),
+        _ => panic!("Could not get network session"),
+    }
+}
+
+/// Remember where the entities are before a tick moves them
+pub fn record_previous_translation(
+    transform: &Transform,
+    mut previous_translation: Mut<PreviousTranslation>,
+) {
+    previous_translation.0 = transform.translation;
+}
+
+/// Render the entities in between the last two ticks, based on how much time has passed since the
+/// last one. This runs after the transforms have been propagated so only the rendered position is
+/// changed.
+pub fn interpolate_translation(
+    fixed_timestep: Res<FixedTimestep>,
+    previous_translation: &PreviousTranslation,
+    transform: &Transform,
+    mut global_transform: Mut<GlobalTransform>,
+) {
+    global_transform.translation = previous_translation
+        .0
+        .lerp(transform.translation, fixed_timestep.alpha());
+}
@@ -0,0 +1,67 @@
+use crate::{
+    components::{Pooled, SheetSprite},
+    resource::TextureAtlasHandles,
+};
+use bevy::prelude::*;
+
+/// Give a sprite that can be drawn to the entities that the simulation shows with a spritesheet
+pub fn attach_sprite(
+    mut commands: Commands,
+    texture_atlas_handles: Res<TextureAtlasHandles>,
+    query: Query<Without<TextureAtlasSprite, (Entity, &SheetSprite, &Transform)>>,
+) {
+    for (entity, sheet_sprite, transform) in query.iter() {
+        // Waits for its animation to pick a sheet
+        let texture_atlas = match sheet_sprite
+            .sheet
+            .as_ref()
+            .and_then(|sheet| texture_atlas_handles.get(sheet))
+        {
+            Some(texture_atlas) => texture_atlas.clone(),
+            None => continue,
+        };
+        commands.insert(
+            entity,
+            SpriteSheetComponents {
+                sprite: TextureAtlasSprite {
+                    index: sheet_sprite.index,
+                    color: sheet_sprite.color,
+                },
+                texture_atlas,
+                transform: *transform,
+                global_transform: GlobalTransform::from(*transform),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// Show the sprite that the simulation picked
+pub fn sync_sprite_index(
+    texture_atlas_handles: Res<TextureAtlasHandles>,
+    sheet_sprite: &SheetSprite,
+    mut sprite: Mut<TextureAtlasSprite>,
+    mut texture_atlas: Mut<Handle<TextureAtlas>>,
+) {
+    // A reused entity can play a clip of another sheet than the one it was created with
+    if let Some(handle) = sheet_sprite
+        .sheet
+        .as_ref()
+        .and_then(|sheet| texture_atlas_handles.get(sheet))
+    {
+        if *texture_atlas != *handle {
+            *texture_atlas = handle.clone();
+        }
+    }
+    sprite.index = sheet_sprite.index;
+    // A reused entity can be tinted differently than the one it was before
+    sprite.color = sheet_sprite.color;
+}
+
+/// Hide the sprites of the entities that wait in a pool, they lose their sheet sprite when they
+/// are put back and get it again when they are reused
+pub fn hide_pooled(mut query: Query<With<Pooled, (Option<&SheetSprite>, &mut Draw)>>) {
+    for (sheet_sprite, mut draw) in query.iter_mut() {
+        draw.is_visible = sheet_sprite.is_some();
+    }
+}
@@ -1,23 +1,77 @@
-use crate::{components::Weapon, events::SpawnLaserEvent};
+use crate::{
+    components::{Laser, Player, Ship, Weapon},
+    entity::release_laser,
+    events::SpawnLaserEvent,
+    resource::{EntityPools, GameTime, PlayerInput, PlayerInputs, Players},
+};
 use bevy::prelude::*;
 
+/// Fire the weapons whose cooldown is over. The enemies' weapons fire on their own, a ship's
+/// weapon only fires while its player holds fire.
 pub fn fire_laser(
-    time: Res<Time>,
+    time: Res<GameTime>,
+    player_inputs: Res<PlayerInputs>,
     mut spawn_laser_events: ResMut<Events<SpawnLaserEvent>>,
-    weapon_parent: &Parent,
-    weapon_global_transform: &GlobalTransform,
-    mut weapon: Mut<Weapon>,
+    query_transform: Query<&Transform>,
+    query_player: Query<&Player>,
+    mut query_weapon: Query<(&Parent, &Transform, &mut Weapon)>,
 ) {
-    weapon.cooldown_timer.tick(time.delta_seconds);
-    if weapon.cooldown_timer.finished {
-        weapon.cooldown_timer.reset();
-        spawn_laser_events.send(SpawnLaserEvent {
-            laser_translation: weapon_global_transform.translation,
-            laser_source: weapon_parent.0,
-            laser_velocity: weapon.laser_velocity,
-            laser_hit_box: weapon.laser_hit_box,
-            laser_time_to_live_duration: weapon.laser_time_to_live_duration,
-            laser_initial_sprite_idx: weapon.laser_initial_sprite_idx,
-        })
+    for (weapon_parent, weapon_transform, mut weapon) in query_weapon.iter_mut() {
+        weapon.cooldown_timer.tick(time.delta_seconds);
+        let trigger_held = query_player.get(weapon_parent.0).map_or(true, |player| {
+            player_inputs.get(*player).pressed(PlayerInput::FIRE)
+        });
+        if weapon.cooldown_timer.finished && trigger_held {
+            weapon.cooldown_timer.reset();
+
+            // Global transforms are only propagated once per frame, so they fall behind when a
+            // frame runs more than one tick
+            let parent_transform = query_transform
+                .get(weapon_parent.0)
+                .expect("Could not get weapon parent transform");
+            spawn_laser_events.send(SpawnLaserEvent {
+                laser_translation: parent_transform.mul_vec3(weapon_transform.translation),
+                laser_source: weapon_parent.0,
+                laser_velocity: weapon.laser_velocity,
+                laser_hit_box: weapon.laser_hit_box,
+                laser_time_to_live_duration: weapon.laser_time_to_live_duration,
+                laser_animation_clip: weapon.laser_animation_clip.clone(),
+                laser_collision_layer: weapon.laser_collision_layer,
+                laser_bounds: weapon.laser_bounds,
+            })
+        }
+    }
+}
+
+/// Clear all the enemies' lasers when a player with a ship and bombs left presses bomb. Holding
+/// bomb only drops one of them.
+pub fn drop_bomb(
+    mut commands: Commands,
+    player_inputs: Res<PlayerInputs>,
+    mut players: ResMut<Players>,
+    mut entity_pools: ResMut<EntityPools>,
+    query_ship: Query<(&Ship, &Player)>,
+    query_laser: Query<(Entity, &Laser)>,
+) {
+    let mut dropped = false;
+    for (_, player) in query_ship.iter() {
+        let player_state = match players.get_mut(*player) {
+            Some(player_state) => player_state,
+            None => continue,
+        };
+        let bomb_pressed = player_inputs.get(*player).pressed(PlayerInput::BOMB);
+        if bomb_pressed && !player_state.bomb_held && player_state.bombs > 0 {
+            player_state.bombs -= 1;
+            dropped = true;
+        }
+        player_state.bomb_held = bomb_pressed;
+    }
+
+    if dropped {
+        for (entity, laser) in query_laser.iter() {
+            if laser.player.is_none() {
+                release_laser(&mut commands, &mut entity_pools.lasers, entity);
+            }
+        }
     }
 }
@@ -0,0 +1,44 @@
+use crate::{
+    components::CameraShake,
+    constant::FULLSCREEN_KEY,
+    resource::{Arena, ArenaView},
+};
+use bevy::{prelude::*, window::WindowMode};
+
+/// Show the whole arena as large as the window allows, with a whole number of pixels per arena
+/// unit. The parts of the window that the arena does not fill are covered by the letterbox bars.
+pub fn fit_arena_to_window(
+    windows: Res<Windows>,
+    arena: Res<Arena>,
+    mut arena_view: ResMut<ArenaView>,
+    mut query_camera: Query<With<CameraShake, &mut Transform>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let window_size = Vec2::new(window.width() as f32, window.height() as f32);
+    if window_size == arena_view.window_size || window_size.x() <= 0. || window_size.y() <= 0. {
+        return;
+    }
+
+    arena_view.window_size = window_size;
+    arena_view.scale = arena.pixel_scale(window_size);
+    for mut transform in query_camera.iter_mut() {
+        transform.scale = Vec3::splat(1. / arena_view.scale);
+    }
+}
+
+/// Switch between a window and the full screen
+pub fn toggle_fullscreen(keyboard_input: Res<Input<KeyCode>>, mut windows: ResMut<Windows>) {
+    if !keyboard_input.just_pressed(FULLSCREEN_KEY) {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        let mode = match window.mode() {
+            WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+            _ => WindowMode::Windowed,
+        };
+        window.set_mode(mode);
+    }
+}
@@ -0,0 +1,184 @@
+use bevy_spaceshooter::{
+    animation::{AnimationError, LayeredAnimations},
+    atlas::{asset_path, SpriteAtlases},
+    components::{Animation, ShipAnimationState},
+    constant::{ANIMATION_MANIFEST_PATH, ATLAS_MANIFEST_PATH, LAYERED_ANIMATION_SHIP},
+    resource::{AnimationClip, AnimationClips, AnimationMode},
+    systems::animation::{advance_animation, next_frame},
+};
+use std::time::Duration;
+
+/// Clips of a ship with a bank channel and a flicker channel, and an explosion on its own sheet
+fn clips() -> AnimationClips {
+    let frame_duration = Duration::from_millis(200);
+    let mut clips = AnimationClips::default();
+    for (index, name) in ["bank_left", "bank_stabilized", "bank_right"]
+        .iter()
+        .enumerate()
+    {
+        clips.insert(
+            name,
+            AnimationClip::uniform("ship", &[index as u32], frame_duration, AnimationMode::Loop),
+        );
+    }
+    clips.insert(
+        "flicker",
+        AnimationClip::uniform("ship", &[0, 1], frame_duration, AnimationMode::Loop),
+    );
+    clips.insert(
+        "explosion",
+        AnimationClip::uniform("explosion", &[0, 1, 2], frame_duration, AnimationMode::Once),
+    );
+    clips
+}
+
+/// A manifest with a ship animation whose channels start with the given clips
+fn manifest(bank_clip: &str, flicker_clip: &str, state_clips: &str) -> String {
+    format!(
+        r#"(layered: {{"ship": (
+            channels: [
+                (name: "bank", stride: 1, initial_clip: "{}"),
+                (name: "flicker", stride: 3, initial_clip: "{}"),
+            ],
+            state_clips: {{{}}},
+        )}})"#,
+        bank_clip, flicker_clip, state_clips
+    )
+}
+
+#[test]
+fn shipped_manifest_plays_declared_clips() {
+    let sprite_atlases =
+        SpriteAtlases::load(asset_path(ATLAS_MANIFEST_PATH)).expect("Could not load atlases");
+    let layered_animations =
+        LayeredAnimations::load(asset_path(ANIMATION_MANIFEST_PATH), sprite_atlases.clips())
+            .expect("Could not load animations");
+    assert!(layered_animations.get(LAYERED_ANIMATION_SHIP).is_some());
+}
+
+#[test]
+fn state_clips_are_looked_up_by_channel() {
+    let content = manifest(
+        "bank_stabilized",
+        "flicker",
+        r#""bank": {FullLeft: "bank_left", Stabilized: "bank_stabilized"}"#,
+    );
+    let layered_animations =
+        LayeredAnimations::parse(&content, &clips()).expect("Could not parse animations");
+    let ship = layered_animations.get("ship").expect("Could not get ship");
+
+    let full_left: Vec<(&str, &str)> = ship.state_clips(ShipAnimationState::FullLeft).collect();
+    assert_eq!(full_left, vec![("bank", "bank_left")]);
+}
+
+#[test]
+fn unknown_initial_clip_is_rejected() {
+    let content = manifest("bank_stabilized", "flame", "");
+    match LayeredAnimations::parse(&content, &clips()) {
+        Err(AnimationError::UnknownClip { animation, clip }) => {
+            assert_eq!(animation, "ship");
+            assert_eq!(clip, "flame");
+        }
+        other => panic!("Expected an unknown clip, got {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_state_clip_is_rejected() {
+    let content = manifest(
+        "bank_stabilized",
+        "flicker",
+        r#""bank": {FullRight: "bank_full_right"}"#,
+    );
+    match LayeredAnimations::parse(&content, &clips()) {
+        Err(AnimationError::UnknownClip { clip, .. }) => assert_eq!(clip, "bank_full_right"),
+        other => panic!("Expected an unknown clip, got {:?}", other),
+    }
+}
+
+#[test]
+fn clips_of_different_sheets_are_rejected() {
+    let content = manifest("bank_stabilized", "explosion", "");
+    match LayeredAnimations::parse(&content, &clips()) {
+        Err(AnimationError::MixedSheets {
+            first_clip,
+            second_clip,
+            ..
+        }) => {
+            assert_eq!(first_clip, "bank_stabilized");
+            assert_eq!(second_clip, "explosion");
+        }
+        other => panic!("Expected mixed sheets, got {:?}", other),
+    }
+}
+
+/// A clip of the given mode whose frames are shown for a tenth of a second each
+fn clip(frames: u32, mode: AnimationMode) -> AnimationClip {
+    let indices: Vec<u32> = (0..frames).collect();
+    AnimationClip::uniform("sheet", &indices, Duration::from_millis(100), mode)
+}
+
+/// The frames that the animation goes through, starting from its first one
+fn frames(clip: &AnimationClip, steps: usize) -> Vec<usize> {
+    let mut animation = Animation::new("clip");
+    let mut frames = vec![animation.frame];
+    for _ in 0..steps {
+        next_frame(clip, &mut animation);
+        frames.push(animation.frame);
+    }
+    frames
+}
+
+#[test]
+fn once_clip_stops_on_its_last_frame() {
+    let clip = clip(3, AnimationMode::Once);
+    assert_eq!(frames(&clip, 4), vec![0, 1, 2, 2, 2]);
+
+    let mut animation = Animation::new("clip");
+    assert!(!advance_animation(&clip, &mut animation, 0.25));
+    assert_eq!(animation.frame, 2);
+    // The clip finishes once the last frame has been shown for its whole duration
+    assert!(advance_animation(&clip, &mut animation, 0.1));
+    assert!(animation.finished);
+    assert_eq!(animation.frame, 2);
+    // A finished clip is only reported once
+    assert!(!advance_animation(&clip, &mut animation, 1.));
+}
+
+#[test]
+fn loop_clip_starts_over() {
+    let clip = clip(3, AnimationMode::Loop);
+    assert_eq!(frames(&clip, 5), vec![0, 1, 2, 0, 1, 2]);
+
+    let mut animation = Animation::new("clip");
+    assert!(!advance_animation(&clip, &mut animation, 1.05));
+    assert!(!animation.finished);
+    assert_eq!(animation.frame, 1);
+}
+
+#[test]
+fn ping_pong_clip_turns_around_on_its_first_and_last_frames() {
+    let clip = clip(3, AnimationMode::PingPong);
+    assert_eq!(frames(&clip, 8), vec![0, 1, 2, 1, 0, 1, 2, 1, 0]);
+
+    let mut animation = Animation::new("clip");
+    assert!(!advance_animation(&clip, &mut animation, 0.35));
+    assert!(animation.reversed);
+    assert_eq!(animation.frame, 1);
+}
+
+#[test]
+fn single_frame_clip_stays_on_its_frame() {
+    for mode in [AnimationMode::Loop, AnimationMode::PingPong].iter() {
+        let clip = clip(1, *mode);
+        assert_eq!(frames(&clip, 3), vec![0, 0, 0, 0]);
+        let mut animation = Animation::new("clip");
+        assert!(!advance_animation(&clip, &mut animation, 1.));
+        assert!(!animation.finished);
+    }
+
+    let clip = clip(1, AnimationMode::Once);
+    let mut animation = Animation::new("clip");
+    assert!(advance_animation(&clip, &mut animation, 0.1));
+    assert_eq!(animation.frame, 0);
+}
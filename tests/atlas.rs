@@ -0,0 +1,150 @@
+use bevy_spaceshooter::{
+    atlas::{asset_path, AtlasError, SpriteAtlases},
+    constant::{ANIMATION_CLIPS_PLAYED, ATLAS_MANIFEST_PATH},
+    resource::AnimationMode,
+};
+
+const SHEETS: &str = r#"
+    "ship": (
+        texture: "spritesheets/ship.png",
+        layout: Grid(tile_size: (16., 24.), columns: 5, rows: 2, names: ["ship_idle"]),
+    ),
+    "laser_bolts": (
+        texture: "spritesheets/laser-bolts.png",
+        layout: Regions(
+            size: (32., 32.),
+            regions: [(name: "ship_laser_01", min: (6., 18.), max: (11., 30.))],
+        ),
+    ),
+"#;
+
+/// A manifest with the sheets above and the given clips
+fn manifest(clips: &str) -> String {
+    format!("(sheets: {{{}}}, clips: {{{}}})", SHEETS, clips)
+}
+
+#[test]
+fn shipped_manifest_has_the_clips_of_the_game() {
+    let sprite_atlases =
+        SpriteAtlases::load(asset_path(ATLAS_MANIFEST_PATH)).expect("Could not load atlases");
+    sprite_atlases
+        .require_clips(&ANIMATION_CLIPS_PLAYED)
+        .expect("Could not find the clips of the game");
+}
+
+#[test]
+fn clip_that_the_game_plays_is_required() {
+    let sprite_atlases = SpriteAtlases::parse(&manifest("")).expect("Could not parse atlases");
+    match sprite_atlases.require_clips(&["explosion"]) {
+        Err(AtlasError::MissingClip { clip }) => assert_eq!(clip, "explosion"),
+        other => panic!("Expected a missing clip, got {:?}", other),
+    }
+}
+
+#[test]
+fn clip_frames_are_resolved_on_their_sheet() {
+    let sprite_atlases = SpriteAtlases::parse(&manifest(
+        r#""idle": (sheet: "ship", frames: [Sprite("ship_idle"), Tile(9)], frame_duration: 0.5,
+            mode: PingPong),"#,
+    ))
+    .expect("Could not parse atlases");
+
+    let clip = sprite_atlases
+        .clips()
+        .get("idle")
+        .expect("Could not get clip");
+    assert_eq!(clip.sheet, "ship");
+    assert_eq!(clip.mode, AnimationMode::PingPong);
+    let indices: Vec<u32> = clip.frames.iter().map(|frame| frame.index).collect();
+    assert_eq!(indices, vec![0, 9]);
+    assert!(clip
+        .frames
+        .iter()
+        .all(|frame| (frame.duration.as_secs_f32() - 0.5).abs() < f32::EPSILON));
+}
+
+#[test]
+fn duplicate_sprite_names_are_rejected() {
+    let content = r#"(sheets: {
+        "first": (texture: "first.png", layout: Grid(tile_size: (8., 8.), columns: 2, rows: 1,
+            names: ["bolt"])),
+        "second": (texture: "second.png", layout: Grid(tile_size: (8., 8.), columns: 2, rows: 1,
+            names: ["bolt"])),
+    })"#;
+    match SpriteAtlases::parse(content) {
+        Err(AtlasError::DuplicateSprite {
+            name,
+            first_sheet,
+            second_sheet,
+        }) => {
+            assert_eq!(name, "bolt");
+            assert_eq!(first_sheet, "first");
+            assert_eq!(second_sheet, "second");
+        }
+        other => panic!("Expected a duplicate sprite, got {:?}", other),
+    }
+}
+
+#[test]
+fn tile_past_the_grid_is_rejected() {
+    let content = manifest(
+        r#""bank": (sheet: "ship", frames: [Tile(10)], frame_duration: 0.2, mode: Loop),"#,
+    );
+    match SpriteAtlases::parse(&content) {
+        Err(AtlasError::TileOutOfRange {
+            clip, index, tiles, ..
+        }) => {
+            assert_eq!(clip, "bank");
+            assert_eq!(index, 10);
+            assert_eq!(tiles, 10);
+        }
+        other => panic!("Expected a tile out of range, got {:?}", other),
+    }
+}
+
+#[test]
+fn clip_on_a_missing_sheet_is_rejected() {
+    let content = manifest(
+        r#""enemy": (sheet: "enemy_small", frames: [Tile(0)], frame_duration: 0.2, mode: Loop),"#,
+    );
+    match SpriteAtlases::parse(&content) {
+        Err(AtlasError::UnknownSheet { clip, sheet }) => {
+            assert_eq!(clip, "enemy");
+            assert_eq!(sheet, "enemy_small");
+        }
+        other => panic!("Expected an unknown sheet, got {:?}", other),
+    }
+}
+
+#[test]
+fn sprite_of_another_sheet_is_rejected() {
+    let content = manifest(
+        r#""laser": (sheet: "ship", frames: [Sprite("ship_laser_01")], frame_duration: 0.2,
+            mode: Loop),"#,
+    );
+    match SpriteAtlases::parse(&content) {
+        Err(AtlasError::UnknownSprite { sprite, sheet, .. }) => {
+            assert_eq!(sprite, "ship_laser_01");
+            assert_eq!(sheet, "ship");
+        }
+        other => panic!("Expected an unknown sprite, got {:?}", other),
+    }
+}
+
+#[test]
+fn clips_need_frames_with_a_duration() {
+    let empty =
+        manifest(r#""none": (sheet: "ship", frames: [], frame_duration: 0.2, mode: Once),"#);
+    assert!(matches!(
+        SpriteAtlases::parse(&empty),
+        Err(AtlasError::EmptyClip { .. })
+    ));
+
+    let instant = manifest(
+        r#""instant": (sheet: "ship", frames: [Tile(0)], frame_duration: 0., mode: Once),"#,
+    );
+    assert!(matches!(
+        SpriteAtlases::parse(&instant),
+        Err(AtlasError::InvalidFrameDuration { .. })
+    ));
+}
@@ -0,0 +1,105 @@
+use bevy_spaceshooter::cli::{CliError, CliOptions};
+use std::path::PathBuf;
+
+fn parse(args: &[&str]) -> Result<CliOptions, CliError> {
+    CliOptions::parse(args.iter().copied())
+}
+
+fn invalid_value(flag: &'static str, value: &str) -> CliError {
+    CliError::InvalidValue {
+        flag,
+        value: value.to_string(),
+    }
+}
+
+#[test]
+fn no_arguments_give_the_default_options() {
+    assert_eq!(parse(&[]), Ok(CliOptions::default()));
+}
+
+#[test]
+fn values_are_given_after_the_flag_or_after_an_equal_sign() {
+    let options = parse(&[
+        "--scale",
+        "3",
+        "--seed=42",
+        "--players",
+        "2",
+        "--record=run.replay",
+        "--god",
+        "--headless",
+    ])
+    .expect("Could not parse arguments");
+    assert_eq!(options.scale, 3.);
+    assert_eq!(options.seed, Some(42));
+    assert_eq!(options.players, 2);
+    assert_eq!(options.record, Some(PathBuf::from("run.replay")));
+    assert!(options.god_mode);
+    assert!(options.headless);
+}
+
+#[test]
+fn unknown_flags_are_rejected() {
+    assert_eq!(
+        parse(&["--fast"]),
+        Err(CliError::UnknownFlag("--fast".to_string()))
+    );
+    assert_eq!(
+        parse(&["--level", "2"]),
+        Err(CliError::UnknownFlag("--level".to_string()))
+    );
+    assert_eq!(
+        parse(&["run.replay"]),
+        Err(CliError::UnknownFlag("run.replay".to_string()))
+    );
+}
+
+#[test]
+fn bad_values_are_rejected() {
+    assert_eq!(parse(&["--seed"]), Err(CliError::MissingValue("--seed")));
+    assert_eq!(
+        parse(&["--seed", "abc"]),
+        Err(invalid_value("--seed", "abc"))
+    );
+    assert_eq!(parse(&["--scale", "0"]), Err(invalid_value("--scale", "0")));
+    assert_eq!(
+        parse(&["--players=3"]),
+        Err(invalid_value("--players", "3"))
+    );
+    assert_eq!(
+        parse(&["--host", "7000", "--packet-loss", "150"]),
+        Err(invalid_value("--packet-loss", "150"))
+    );
+    assert_eq!(
+        parse(&["--god=yes"]),
+        Err(CliError::UnexpectedValue("--god"))
+    );
+}
+
+#[test]
+fn conflicting_flags_are_rejected() {
+    assert_eq!(
+        parse(&["--record", "a.replay", "--replay", "b.replay"]),
+        Err(CliError::Conflict("--record", "--replay"))
+    );
+    assert_eq!(
+        parse(&["--seed", "1", "--replay", "b.replay"]),
+        Err(CliError::Conflict("--seed", "--replay"))
+    );
+    assert_eq!(
+        parse(&["--host", "7000", "--connect", "127.0.0.1:7000"]),
+        Err(CliError::Conflict("--host", "--connect"))
+    );
+    assert_eq!(
+        parse(&["--host", "7000", "--record", "a.replay"]),
+        Err(CliError::Conflict("--record", "--host"))
+    );
+    assert_eq!(
+        parse(&["--connect", "127.0.0.1:7000", "--seed", "1"]),
+        Err(CliError::Conflict("--seed", "--connect"))
+    );
+    assert_eq!(
+        parse(&["--latency", "100"]),
+        Err(CliError::NotNetworked("--latency"))
+    );
+}
@@ -0,0 +1,196 @@
+use bevy::math::Vec2;
+use bevy_spaceshooter::{collision::Shape, components::Collider};
+
+fn circle(x: f32, y: f32, radius: f32) -> Shape {
+    Shape::Circle {
+        center: Vec2::new(x, y),
+        radius,
+    }
+}
+
+fn aabb(x: f32, y: f32, half_width: f32, half_height: f32) -> Shape {
+    Shape::Aabb {
+        center: Vec2::new(x, y),
+        half_extents: Vec2::new(half_width, half_height),
+    }
+}
+
+fn capsule(start: (f32, f32), end: (f32, f32), radius: f32) -> Shape {
+    Shape::Capsule {
+        start: Vec2::new(start.0, start.1),
+        end: Vec2::new(end.0, end.1),
+        radius,
+    }
+}
+
+/// Intersection does not depend on which shape is tested against the other
+fn intersects(shape: Shape, other: Shape) -> bool {
+    let result = shape.intersects(&other);
+    assert_eq!(other.intersects(&shape), result, "{:?} {:?}", shape, other);
+    result
+}
+
+#[test]
+fn circle_and_circle() {
+    assert!(intersects(circle(0., 0., 2.), circle(3., 0., 2.)));
+    assert!(!intersects(circle(0., 0., 2.), circle(3., 3., 2.)));
+    // Touching is not intersecting
+    assert!(!intersects(circle(0., 0., 2.), circle(4., 0., 2.)));
+}
+
+#[test]
+fn circle_and_aabb() {
+    assert!(intersects(circle(0., 0., 1.), aabb(0., 0., 5., 5.)));
+    assert!(intersects(circle(6., 0., 2.), aabb(0., 0., 5., 5.)));
+    // Close to the corner of the box but outside of its rounding
+    assert!(!intersects(circle(6.5, 6.5, 2.), aabb(0., 0., 5., 5.)));
+}
+
+#[test]
+fn circle_and_capsule() {
+    let vertical = capsule((0., -5.), (0., 5.), 1.);
+    assert!(intersects(circle(2., 4., 1.5), vertical));
+    assert!(!intersects(circle(3., 0., 1.5), vertical));
+    // Past the end of the segment, only the rounding is left
+    assert!(intersects(circle(0., 7., 1.5), vertical));
+    assert!(!intersects(circle(0., 8., 1.5), vertical));
+}
+
+#[test]
+fn aabb_and_aabb() {
+    assert!(intersects(aabb(0., 0., 2., 2.), aabb(3., 3., 2., 2.)));
+    assert!(!intersects(aabb(0., 0., 2., 2.), aabb(3., 5., 2., 2.)));
+    assert!(!intersects(aabb(0., 0., 2., 2.), aabb(4., 0., 2., 2.)));
+}
+
+#[test]
+fn aabb_and_capsule() {
+    let wide = aabb(0., 0., 8., 2.);
+    // The segment crosses the box without either end in it
+    assert!(intersects(wide, capsule((-1., -10.), (1., 10.), 0.5)));
+    // Inside of the box
+    assert!(intersects(wide, capsule((-1., 0.), (1., 0.), 0.5)));
+    // Beside the box, the rounding reaches it
+    assert!(intersects(wide, capsule((9., -10.), (9., 10.), 1.5)));
+    assert!(!intersects(wide, capsule((10., -10.), (10., 10.), 1.5)));
+    // Past the corner of the box
+    assert!(!intersects(wide, capsule((9., 4.), (12., 1.), 0.5)));
+}
+
+#[test]
+fn capsule_and_capsule() {
+    let vertical = capsule((0., -5.), (0., 5.), 1.);
+    assert!(intersects(vertical, capsule((-5., 0.), (5., 0.), 0.1)));
+    assert!(intersects(vertical, capsule((1.5, -5.), (1.5, 5.), 1.)));
+    assert!(!intersects(vertical, capsule((2.5, -5.), (2.5, 5.), 0.4)));
+    // On the same line, one after the other
+    assert!(intersects(vertical, capsule((0., 6.), (0., 10.), 0.5)));
+    assert!(!intersects(vertical, capsule((0., 7.), (0., 10.), 0.5)));
+}
+
+#[test]
+fn colliders_are_placed_at_their_offset() {
+    let offset_circle = Collider::Circle {
+        offset: Vec2::new(10., 0.),
+        radius: 1.,
+    };
+    let point = Collider::circle(0.5);
+    assert!(offset_circle.intersects(Vec2::zero(), &point, Vec2::new(10., 0.)));
+    assert!(!offset_circle.intersects(Vec2::zero(), &point, Vec2::zero()));
+
+    let offset_aabb = Collider::Aabb {
+        offset: Vec2::new(0., -4.),
+        half_extents: Vec2::new(1., 1.),
+    };
+    assert!(offset_aabb.intersects(Vec2::new(5., 5.), &point, Vec2::new(5., 1.)));
+    assert!(!offset_aabb.intersects(Vec2::new(5., 5.), &point, Vec2::new(5., 5.)));
+
+    let offset_capsule = Collider::Capsule {
+        offset: Vec2::new(3., 0.),
+        half_segment: Vec2::new(0., 2.),
+        radius: 1.,
+    };
+    assert!(offset_capsule.intersects(Vec2::zero(), &point, Vec2::new(3., 2.5)));
+    assert!(!offset_capsule.intersects(Vec2::zero(), &point, Vec2::new(0., 2.5)));
+}
+
+#[test]
+fn compound_collides_with_any_of_its_parts() {
+    // Two wings on the sides of an empty middle
+    let wings = Collider::Compound {
+        offset: Vec2::new(0., 1.),
+        parts: vec![
+            Collider::Circle {
+                offset: Vec2::new(-5., 0.),
+                radius: 2.,
+            },
+            Collider::Aabb {
+                offset: Vec2::new(5., 0.),
+                half_extents: Vec2::new(2., 2.),
+            },
+        ],
+    };
+    let point = Collider::circle(0.5);
+    assert!(wings.intersects(Vec2::zero(), &point, Vec2::new(-5., 1.)));
+    assert!(wings.intersects(Vec2::zero(), &point, Vec2::new(5., 2.)));
+    assert!(!wings.intersects(Vec2::zero(), &point, Vec2::new(0., 1.)));
+    assert!(point.intersects(Vec2::new(5., 2.), &wings, Vec2::zero()));
+
+    let nested = Collider::Compound {
+        offset: Vec2::new(100., 0.),
+        parts: vec![wings.clone()],
+    };
+    assert!(nested.intersects(Vec2::zero(), &point, Vec2::new(95., 1.)));
+    assert!(nested.intersects(Vec2::zero(), &wings, Vec2::new(100., 0.)));
+}
+
+#[test]
+fn capsule_fills_its_box_along_the_longest_side() {
+    let tall = Collider::capsule_in(Vec2::new(4., 12.));
+    let point = Collider::circle(0.1);
+    assert!(tall.intersects(Vec2::zero(), &point, Vec2::new(0., 5.8)));
+    assert!(!tall.intersects(Vec2::zero(), &point, Vec2::new(0., 6.2)));
+    // The corners of the box are rounded off
+    assert!(!tall.intersects(Vec2::zero(), &point, Vec2::new(1.9, 5.9)));
+
+    let wide = Collider::capsule_in(Vec2::new(12., 4.));
+    assert!(wide.intersects(Vec2::zero(), &point, Vec2::new(5.8, 0.)));
+    assert!(!wide.intersects(Vec2::zero(), &point, Vec2::new(0., 2.2)));
+}
+
+#[test]
+fn swept_shapes_hit_what_they_went_through() {
+    let thin_wall = aabb(0., 0., 5., 0.5);
+
+    let circle_after = circle(0., 10., 1.);
+    assert!(!intersects(circle_after, thin_wall));
+    assert!(intersects(
+        circle_after.swept(Vec2::new(0., 20.)),
+        thin_wall
+    ));
+    assert!(!intersects(
+        circle_after.swept(Vec2::new(0., 5.)),
+        thin_wall
+    ));
+
+    // Moving along its segment, the capsule is swept exactly
+    let capsule_after = capsule((0., 48.), (0., 52.), 1.);
+    let swept_capsule = capsule_after.swept(Vec2::new(0., 100.));
+    assert!(intersects(swept_capsule, thin_wall));
+    assert!(!intersects(swept_capsule, aabb(0., 60., 5., 0.5)));
+    assert!(!intersects(swept_capsule, aabb(0., -60., 5., 0.5)));
+    // Moving sideways
+    let sideways = capsule((10., -2.), (10., 2.), 1.).swept(Vec2::new(20., 0.));
+    assert!(intersects(sideways, aabb(0., 0., 1., 1.)));
+    assert!(!intersects(sideways, aabb(0., 10., 1., 1.)));
+
+    let aabb_after = aabb(0., 20., 1., 1.);
+    assert!(intersects(
+        aabb_after.swept(Vec2::new(0., 40.)),
+        circle(0., 0., 0.5)
+    ));
+    assert!(!intersects(
+        aabb_after.swept(Vec2::new(0., 40.)),
+        circle(5., 0., 0.5)
+    ));
+}
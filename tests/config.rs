@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_spaceshooter::{
+    atlas::asset_path,
+    components::{Collider, HitBox, Ship},
+    config::{ConfigError, GameConfig},
+    constant::CONFIG_PATH,
+    events::ConfigReloadedEvent,
+    game::HeadlessGame,
+};
+
+/// The field that a config is rejected for
+fn invalid_field(content: &str) -> &'static str {
+    match GameConfig::parse(content) {
+        Err(ConfigError::Invalid { field, .. }) => field,
+        other => panic!("Expected an invalid field, got {:?}", other),
+    }
+}
+
+#[test]
+fn default_and_shipped_configs_are_valid() {
+    GameConfig::default()
+        .validate()
+        .expect("Could not validate default config");
+    GameConfig::load(asset_path(CONFIG_PATH)).expect("Could not load shipped config");
+}
+
+#[test]
+fn invalid_values_are_reported_with_their_field() {
+    assert_eq!(invalid_field("(arena: (0., 240.))"), "arena");
+    assert_eq!(invalid_field("(ship: (lives: 0))"), "ship.lives");
+    assert_eq!(
+        invalid_field("(ship: (core_radius: -1.))"),
+        "ship.core_radius"
+    );
+    assert_eq!(
+        invalid_field("(enemy: (spawn_weights: (small: 0, medium: 0, big: 0)))"),
+        "enemy.spawn_weights"
+    );
+    assert_eq!(
+        invalid_field(
+            "(enemy: (laser: (size: (5., 5.), velocity: (0., -50.), cooldown: 0., \
+             time_to_live: 5.)))"
+        ),
+        "enemy.laser.cooldown"
+    );
+    assert_eq!(
+        invalid_field("(camera_shake: (trauma_ship: 1.5))"),
+        "camera_shake.trauma_ship"
+    );
+}
+
+#[test]
+fn error_message_names_the_field() {
+    let err = GameConfig::parse("(hit_stop: (ship: -0.1))").expect_err("Config should be invalid");
+    assert_eq!(err.to_string(), "`hit_stop.ship` must not be negative");
+}
+
+#[test]
+fn camera_shake_can_be_turned_off() {
+    assert!(GameConfig::default().accessibility.camera_shake);
+    let config = GameConfig::parse("(accessibility: (camera_shake: false))")
+        .expect("Could not parse config");
+    assert!(!config.accessibility.camera_shake);
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    match GameConfig::parse("(ship: (speed: 10.))") {
+        Err(ConfigError::Parse(_)) => {}
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+}
+
+// Configs are only reloaded in debug builds
+#[cfg(debug_assertions)]
+#[test]
+fn reloaded_ship_size_applies_to_the_ships_that_are_there() {
+    let mut game = HeadlessGame::new(GameConfig::default(), 0);
+    {
+        let mut config = game
+            .app
+            .resources
+            .get_mut::<GameConfig>()
+            .expect("Could not get config");
+        config.ship.size = (20., 30.);
+        config.ship.core_radius = 5.;
+    }
+    game.app
+        .resources
+        .get_mut::<Events<ConfigReloadedEvent>>()
+        .expect("Could not get config reloaded events")
+        .send(ConfigReloadedEvent);
+    game.step(1);
+
+    let (hit_box, collider) = game
+        .app
+        .world
+        .query::<(&Ship, &HitBox, &Collider)>()
+        .map(|(_, hit_box, collider)| (hit_box.0, collider.clone()))
+        .next()
+        .expect("Could not find ship");
+    assert_eq!(hit_box, Vec2::new(20., 30.));
+    assert_eq!(collider, Collider::circle(5.));
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy_spaceshooter::{
+    components::Player,
+    config::GameConfig,
+    game::HeadlessGame,
+    resource::{GameRng, InputSource, PlayerInput, PlayerInputs, Players},
+};
+use rand::RngCore;
+
+/// Everything that two runs have to agree on: where every entity is, the players' state and the
+/// next number that the random number generator gives
+#[derive(Debug, PartialEq)]
+struct GameState {
+    translations: Vec<(Entity, Vec3)>,
+    players: String,
+    next_random: u64,
+}
+
+fn game_state(game: &HeadlessGame) -> GameState {
+    let mut translations: Vec<(Entity, Vec3)> = game
+        .app
+        .world
+        .query::<(Entity, &Transform)>()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+    translations.sort_by_key(|(entity, _)| *entity);
+    let players = game
+        .app
+        .resources
+        .get::<Players>()
+        .expect("Could not get players");
+    let mut rng = game
+        .app
+        .resources
+        .get::<GameRng>()
+        .expect("Could not get game rng")
+        .clone();
+    GameState {
+        translations,
+        players: format!("{:?}", *players),
+        next_random: rng.next_u64(),
+    }
+}
+
+/// The input of the given tick, the ship weaves from side to side and focuses now and then
+fn scripted_input(tick: u32) -> PlayerInput {
+    let direction = if (tick / 45) % 2 == 0 {
+        PlayerInput::LEFT
+    } else {
+        PlayerInput::RIGHT
+    };
+    let focus = if tick % 100 < 30 {
+        PlayerInput::FOCUS
+    } else {
+        0
+    };
+    PlayerInput::from_bits(direction | focus)
+}
+
+/// Run a game with the enemy spawner for the given number of ticks
+fn run(seed: u64, ticks: u32) -> GameState {
+    let mut game = HeadlessGame::new(GameConfig::default(), seed);
+    game.app.resources.insert(InputSource::Scripted);
+    for tick in 0..ticks {
+        game.app
+            .resources
+            .get_mut::<PlayerInputs>()
+            .expect("Could not get player inputs")
+            .0[Player(0).0] = scripted_input(tick);
+        game.step(1);
+    }
+    game_state(&game)
+}
+
+#[test]
+fn same_seed_and_inputs_give_the_same_game() {
+    let first = run(7, 600);
+    let second = run(7, 600);
+    // Enemies have been spawned, so the spawner's random decisions are part of the comparison
+    assert!(first.translations.len() > 5);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn different_seeds_give_different_games() {
+    assert_ne!(run(7, 600), run(8, 600));
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use bevy_spaceshooter::{
+    components::CameraShake,
+    config::{AccessibilityConfig, GameConfig},
+    resource::AccessibilitySettings,
+    systems::feedback::camera_shake,
+};
+
+/// An app that only shakes a camera with full trauma, returns the camera's transform after a few
+/// frames
+fn shaken_camera_transform(accessibility_config: AccessibilityConfig) -> Transform {
+    let mut app_builder = App::build();
+    app_builder
+        .add_plugins(MinimalPlugins)
+        .add_resource(AccessibilitySettings::new(&accessibility_config))
+        .add_system(camera_shake.system());
+    let mut app = std::mem::take(&mut app_builder.app);
+    let camera_shake_config = GameConfig::default().camera_shake;
+    let camera = app.world.spawn((
+        CameraShake {
+            trauma: 1.,
+            trauma_decay: camera_shake_config.trauma_decay,
+            max_offset: camera_shake_config.max_offset,
+            max_roll: camera_shake_config.max_roll,
+            frequency: camera_shake_config.frequency,
+        },
+        Transform::default(),
+    ));
+    for _ in 0..3 {
+        app.update();
+    }
+    *app.world
+        .get::<Transform>(camera)
+        .expect("Could not get camera transform")
+}
+
+#[test]
+fn camera_does_not_shake_when_the_setting_is_off() {
+    let transform = shaken_camera_transform(AccessibilityConfig {
+        camera_shake: false,
+    });
+    assert_eq!(transform, Transform::default());
+}
+
+#[test]
+fn camera_shakes_when_the_setting_is_on() {
+    let transform = shaken_camera_transform(AccessibilityConfig { camera_shake: true });
+    assert_ne!(transform, Transform::default());
+}
@@ -0,0 +1,856 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_spaceshooter::{
+    components::{
+        ArenaBounds, BoundsPolicy, CollisionLayer, Enemy, EnemySpawner, EnemyVariant, Explosion,
+        HitBox, Laser, LayeredAnimation, Player, Pooled, PreviousTranslation, Ship,
+        ShipAnimationState, Velocity, Weapon,
+    },
+    config::GameConfig,
+    constant::{ANIMATION_CLIP_SHIP_LASER, ARENA_HEIGHT, ARENA_WIDTH},
+    events::{CollisionLaserEnemyEvent, SpawnEnemyEvent, SpawnLaserEvent},
+    game::HeadlessGame,
+    resource::{Arena, EntityPools, InputSource, PlayerInput, PlayerInputs, Players},
+};
+use std::time::Duration;
+
+/// A game with only the ship in it, the ship's input is set by the test
+fn game_without_spawner() -> HeadlessGame {
+    game_without_spawner_with_config(GameConfig::default())
+}
+
+fn game_without_spawner_with_config(config: GameConfig) -> HeadlessGame {
+    let mut game = HeadlessGame::new(config, 0);
+    let spawners: Vec<Entity> = game
+        .app
+        .world
+        .query::<(Entity, &EnemySpawner)>()
+        .map(|(entity, _)| entity)
+        .collect();
+    for spawner in spawners {
+        game.app
+            .world
+            .despawn(spawner)
+            .expect("Could not despawn enemy spawner");
+    }
+    game.app.resources.insert(InputSource::Scripted);
+    game
+}
+
+fn ship(game: &HeadlessGame) -> Entity {
+    game.app
+        .world
+        .query::<(Entity, &Ship)>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("Could not find ship")
+}
+
+fn player_ship(game: &HeadlessGame, player: Player) -> Option<Entity> {
+    game.app
+        .world
+        .query::<(Entity, &Ship, &Player)>()
+        .filter(|(_, _, ship_player)| **ship_player == player)
+        .map(|(entity, _, _)| entity)
+        .next()
+}
+
+fn ship_translation(game: &HeadlessGame) -> Vec3 {
+    game.app
+        .world
+        .query::<(&Ship, &Transform)>()
+        .map(|(_, transform)| transform.translation)
+        .next()
+        .expect("Could not find ship")
+}
+
+fn ship_animation_state(game: &HeadlessGame) -> ShipAnimationState {
+    game.app
+        .world
+        .query::<&Ship>()
+        .map(|ship| ship.animation_state)
+        .next()
+        .expect("Could not find ship")
+}
+
+fn count<T: Send + Sync + 'static>(game: &HeadlessGame) -> usize {
+    game.app.world.query::<&T>().count()
+}
+
+fn set_player_input(game: &mut HeadlessGame, actions: u8) {
+    set_input_of(game, Player(0), actions);
+}
+
+fn set_input_of(game: &mut HeadlessGame, player: Player, actions: u8) {
+    game.app
+        .resources
+        .get_mut::<PlayerInputs>()
+        .expect("Could not get player inputs")
+        .0[player.0] = PlayerInput::from_bits(actions);
+}
+
+fn score_and_lives(game: &HeadlessGame, player: Player) -> Option<(u32, u32)> {
+    game.app
+        .resources
+        .get::<Players>()
+        .expect("Could not get players")
+        .0[player.0]
+        .as_ref()
+        .map(|player_state| (player_state.score, player_state.lives))
+}
+
+/// A game with both players in it, the second one joined by pressing fire
+fn game_with_two_players(config: GameConfig) -> HeadlessGame {
+    let mut game = game_without_spawner_with_config(config);
+    set_input_of(&mut game, Player(1), PlayerInput::FIRE);
+    game.step(1);
+    set_input_of(&mut game, Player(1), 0);
+    game
+}
+
+fn spawn_enemy(game: &mut HeadlessGame, enemy_variant: EnemyVariant, enemy_translation: Vec3) {
+    game.app
+        .resources
+        .get_mut::<Events<SpawnEnemyEvent>>()
+        .expect("Could not get spawn enemy events")
+        .send(SpawnEnemyEvent {
+            enemy_variant,
+            enemy_translation,
+        });
+}
+
+fn spawn_laser(
+    game: &mut HeadlessGame,
+    laser_source: Entity,
+    laser_translation: Vec3,
+    laser_collision_layer: CollisionLayer,
+) {
+    send_spawn_laser(
+        game,
+        laser_event(laser_source, laser_translation, laser_collision_layer),
+    );
+}
+
+/// A laser that stands still, it is removed once it is out of the arena
+fn laser_event(
+    laser_source: Entity,
+    laser_translation: Vec3,
+    laser_collision_layer: CollisionLayer,
+) -> SpawnLaserEvent {
+    SpawnLaserEvent {
+        laser_source,
+        laser_translation,
+        laser_velocity: Velocity(Vec2::zero()),
+        laser_hit_box: HitBox(Vec2::new(4., 4.)),
+        laser_time_to_live_duration: Duration::from_secs(10),
+        laser_animation_clip: ANIMATION_CLIP_SHIP_LASER.to_string(),
+        laser_collision_layer,
+        laser_bounds: ArenaBounds::new(BoundsPolicy::Despawn, 0.),
+    }
+}
+
+fn send_spawn_laser(game: &mut HeadlessGame, spawn_laser_event: SpawnLaserEvent) {
+    game.app
+        .resources
+        .get_mut::<Events<SpawnLaserEvent>>()
+        .expect("Could not get spawn laser events")
+        .send(spawn_laser_event);
+}
+
+/// Spawn a moving laser of the ship with the given bounds
+fn spawn_moving_laser(
+    game: &mut HeadlessGame,
+    laser_translation: Vec3,
+    laser_velocity: Vec2,
+    laser_bounds: ArenaBounds,
+) {
+    let ship = ship(game);
+    send_spawn_laser(
+        game,
+        SpawnLaserEvent {
+            laser_velocity: Velocity(laser_velocity),
+            laser_bounds,
+            ..laser_event(ship, laser_translation, CollisionLayer::ship_laser())
+        },
+    );
+}
+
+/// Translation and velocity of the only laser
+fn laser_motion(game: &HeadlessGame) -> (Vec3, Vec2) {
+    game.app
+        .world
+        .query::<(&Laser, &Transform, &Velocity)>()
+        .map(|(_, transform, velocity)| (transform.translation, velocity.0))
+        .next()
+        .expect("Could not find laser")
+}
+
+fn app_exit_sent(game: &HeadlessGame) -> bool {
+    let app_exit_events = game
+        .app
+        .resources
+        .get::<Events<AppExit>>()
+        .expect("Could not get app exit events");
+    app_exit_events
+        .get_reader()
+        .iter(&app_exit_events)
+        .next()
+        .is_some()
+}
+
+/// Collect the banking states that the ship goes through while the same input is held
+fn bank_states(game: &mut HeadlessGame, actions: u8, ticks: u32) -> Vec<ShipAnimationState> {
+    set_player_input(game, actions);
+    let mut states = vec![ship_animation_state(game)];
+    for _ in 0..ticks {
+        game.step(1);
+        let state = ship_animation_state(game);
+        if states.last() != Some(&state) {
+            states.push(state);
+        }
+    }
+    states
+}
+
+#[test]
+fn laser_hitting_enemy_removes_both_and_spawns_explosion() {
+    let mut game = game_without_spawner();
+    // Away from the ship's line of fire
+    let enemy_translation = Vec3::new(-60., 60., 0.);
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, enemy_translation);
+    spawn_laser(
+        &mut game,
+        ship,
+        enemy_translation,
+        CollisionLayer::ship_laser(),
+    );
+
+    game.step(1);
+    assert_eq!(count::<Enemy>(&game), 0);
+    game.step(1);
+    assert_eq!(count::<Explosion>(&game), 1);
+    assert_eq!(
+        game.app
+            .world
+            .query::<(&Laser, &Transform)>()
+            .filter(|(_, transform)| transform.translation.x() < -30.)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn ship_is_never_hit_by_its_own_laser() {
+    let mut game = game_without_spawner();
+    let ship = ship(&game);
+    spawn_laser(
+        &mut game,
+        ship,
+        ship_translation(&game),
+        CollisionLayer::ship_laser(),
+    );
+
+    game.step(10);
+    assert_eq!(count::<Ship>(&game), 1);
+    assert!(!app_exit_sent(&game));
+}
+
+#[test]
+fn ship_is_destroyed_by_another_entity_laser() {
+    let mut game = game_without_spawner();
+    let other_source = game.app.world.spawn((Transform::default(),));
+    spawn_laser(
+        &mut game,
+        other_source,
+        ship_translation(&game),
+        CollisionLayer::enemy_laser(),
+    );
+
+    game.step(2);
+    assert_eq!(count::<Ship>(&game), 0);
+    assert!(app_exit_sent(&game));
+}
+
+#[test]
+fn enemy_laser_never_hits_enemies_after_its_source_is_gone() {
+    let mut game = game_without_spawner();
+    let enemy_translation = Vec3::new(-60., 60., 0.);
+    let source = game.app.world.spawn((Transform::default(),));
+    spawn_enemy(&mut game, EnemyVariant::Small, enemy_translation);
+    spawn_laser(
+        &mut game,
+        source,
+        enemy_translation,
+        CollisionLayer::enemy_laser(),
+    );
+    game.step(1);
+    game.app
+        .world
+        .despawn(source)
+        .expect("Could not despawn laser source");
+
+    game.step(10);
+    assert_eq!(count::<Enemy>(&game), 1);
+    assert_eq!(count::<Explosion>(&game), 0);
+}
+
+#[test]
+fn fast_laser_hits_the_enemy_it_goes_through() {
+    let mut game = game_without_spawner();
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, Vec3::new(-60., 60., 0.));
+    spawn_laser(
+        &mut game,
+        ship,
+        Vec3::new(-60., -60., 0.),
+        CollisionLayer::ship_laser(),
+    );
+    game.step(1);
+
+    // Far enough in one tick to end up past the enemy
+    let laser = game
+        .app
+        .world
+        .query::<(Entity, &Laser)>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("Could not find laser");
+    game.app
+        .world
+        .insert_one(laser, Velocity(Vec2::new(0., 12_000.)))
+        .expect("Could not set laser velocity");
+    game.step(1);
+    assert_eq!(count::<Enemy>(&game), 0);
+}
+
+#[test]
+fn fast_laser_hits_the_first_enemy_on_its_way() {
+    let mut game = game_without_spawner();
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, Vec3::new(-60., 30., 0.));
+    spawn_enemy(&mut game, EnemyVariant::Small, Vec3::new(-60., 60., 0.));
+    spawn_laser(
+        &mut game,
+        ship,
+        Vec3::new(-60., 110., 0.),
+        CollisionLayer::ship_laser(),
+    );
+    game.step(1);
+
+    // Goes down through both enemies in one tick, the upper one is on its way first
+    let laser = game
+        .app
+        .world
+        .query::<(Entity, &Laser)>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("Could not find laser");
+    game.app
+        .world
+        .insert_one(laser, Velocity(Vec2::new(0., -6_000.)))
+        .expect("Could not set laser velocity");
+    game.step(1);
+
+    let enemies: Vec<f32> = game
+        .app
+        .world
+        .query::<(&Enemy, &Transform)>()
+        .map(|(_, transform)| transform.translation.y())
+        .collect();
+    assert_eq!(enemies.len(), 1);
+    assert!(enemies[0] < 45.);
+}
+
+#[test]
+fn enemy_hit_by_two_lasers_is_destroyed_once() {
+    let mut game = game_without_spawner();
+    let enemy_translation = Vec3::new(-60., 60., 0.);
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, enemy_translation);
+    for _ in 0..2 {
+        spawn_laser(
+            &mut game,
+            ship,
+            enemy_translation,
+            CollisionLayer::ship_laser(),
+        );
+    }
+
+    game.step(2);
+    assert_eq!(count::<Enemy>(&game), 0);
+    assert_eq!(count::<Explosion>(&game), 1);
+    // The second laser had nothing left to hit
+    assert_eq!(count::<Laser>(&game), 1);
+    assert_eq!(
+        score_and_lives(&game, Player(0)).map(|(score, _)| score),
+        Some(*GameConfig::default().enemy.scores.get(EnemyVariant::Small))
+    );
+}
+
+#[test]
+fn laser_touching_two_enemies_destroys_only_one() {
+    let mut game = game_without_spawner();
+    let enemy_translation = Vec3::new(-60., 60., 0.);
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, enemy_translation);
+    spawn_enemy(&mut game, EnemyVariant::Small, enemy_translation);
+    spawn_laser(
+        &mut game,
+        ship,
+        enemy_translation,
+        CollisionLayer::ship_laser(),
+    );
+
+    game.step(2);
+    assert_eq!(count::<Enemy>(&game), 1);
+    assert_eq!(count::<Explosion>(&game), 1);
+    assert_eq!(count::<Laser>(&game), 0);
+}
+
+#[test]
+fn hits_on_entities_that_are_gone_are_left_out() {
+    let mut game = game_without_spawner();
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, Vec3::new(-60., 60., 0.));
+    spawn_laser(
+        &mut game,
+        ship,
+        Vec3::new(60., 60., 0.),
+        CollisionLayer::ship_laser(),
+    );
+    game.step(1);
+
+    let laser = game
+        .app
+        .world
+        .query::<(Entity, &Laser)>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("Could not find laser");
+    let enemy = game
+        .app
+        .world
+        .query::<(Entity, &Enemy)>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("Could not find enemy");
+    let gone = game.app.world.spawn((Transform::default(),));
+    game.app
+        .world
+        .despawn(gone)
+        .expect("Could not despawn entity");
+    {
+        let mut collision_laser_enemy_events = game
+            .app
+            .resources
+            .get_mut::<Events<CollisionLaserEnemyEvent>>()
+            .expect("Could not get collision events");
+        collision_laser_enemy_events.send(CollisionLaserEnemyEvent {
+            laser_entity: laser,
+            enemy_entity: gone,
+        });
+        collision_laser_enemy_events.send(CollisionLaserEnemyEvent {
+            laser_entity: gone,
+            enemy_entity: enemy,
+        });
+    }
+
+    game.step(1);
+    assert_eq!(count::<Laser>(&game), 1);
+    assert_eq!(count::<Enemy>(&game), 1);
+    assert_eq!(count::<Explosion>(&game), 0);
+}
+
+/// Hits and misses of the laser pool and of the explosion pool
+fn pool_metrics(game: &HeadlessGame) -> [(u64, u64); 2] {
+    let entity_pools = game
+        .app
+        .resources
+        .get::<EntityPools>()
+        .expect("Could not get entity pools");
+    [
+        (entity_pools.lasers.hits(), entity_pools.lasers.misses()),
+        (
+            entity_pools.explosions.hits(),
+            entity_pools.explosions.misses(),
+        ),
+    ]
+}
+
+/// Shoot an enemy and wait for its explosion to be over
+fn destroy_enemy(game: &mut HeadlessGame, enemy_translation: Vec3) {
+    let ship = ship(game);
+    spawn_enemy(game, EnemyVariant::Small, enemy_translation);
+    spawn_laser(game, ship, enemy_translation, CollisionLayer::ship_laser());
+    game.step(2);
+    assert_eq!(count::<Enemy>(game), 0);
+    game.step(120);
+    assert_eq!(count::<Explosion>(game), 0);
+}
+
+#[test]
+fn lasers_and_explosions_are_reused_from_their_pools() {
+    let mut game = game_without_spawner();
+    destroy_enemy(&mut game, Vec3::new(-60., 60., 0.));
+    assert_eq!(pool_metrics(&game), [(0, 1), (0, 1)]);
+    // The laser and the explosion wait in their pools
+    assert_eq!(count::<Laser>(&game), 0);
+    assert_eq!(count::<Pooled>(&game), 2);
+
+    destroy_enemy(&mut game, Vec3::new(60., 60., 0.));
+    assert_eq!(pool_metrics(&game), [(1, 1), (1, 1)]);
+    assert_eq!(count::<Pooled>(&game), 2);
+}
+
+#[test]
+fn explosion_goes_back_to_its_pool_when_its_clip_is_over() {
+    let mut game = game_without_spawner();
+    let enemy_translation = Vec3::new(-60., 60., 0.);
+    let ship = ship(&game);
+    spawn_enemy(&mut game, EnemyVariant::Small, enemy_translation);
+    spawn_laser(
+        &mut game,
+        ship,
+        enemy_translation,
+        CollisionLayer::ship_laser(),
+    );
+    game.step(2);
+    let explosion = game
+        .app
+        .world
+        .query::<(Entity, &Explosion)>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("Could not find explosion");
+
+    // The explosion's clip shows 5 frames of 0.2 seconds once
+    game.step(55);
+    assert!(game.app.world.get::<Explosion>(explosion).is_ok());
+    game.step(10);
+    assert!(game.app.world.get::<Explosion>(explosion).is_err());
+    assert!(game.app.world.get::<Pooled>(explosion).is_ok());
+}
+
+#[test]
+fn exhausted_pools_spawn_new_entities() {
+    let mut config = GameConfig::default();
+    config.pools.lasers = 0;
+    config.pools.explosions = 0;
+    let mut game = game_without_spawner_with_config(config);
+    destroy_enemy(&mut game, Vec3::new(-60., 60., 0.));
+    destroy_enemy(&mut game, Vec3::new(60., 60., 0.));
+    assert_eq!(pool_metrics(&game), [(0, 2), (0, 2)]);
+    // Nothing fits in the pools, so the entities are despawned
+    assert_eq!(count::<Pooled>(&game), 0);
+}
+
+#[test]
+fn expired_laser_goes_back_to_its_pool() {
+    let mut game = game_without_spawner();
+    let ship = ship(&game);
+    spawn_laser(
+        &mut game,
+        ship,
+        Vec3::new(-60., 60., 0.),
+        CollisionLayer::ship_laser(),
+    );
+    game.step(1);
+    assert_eq!(count::<Laser>(&game), 1);
+
+    // The laser lives for 10 seconds
+    game.step(11 * 60);
+    assert_eq!(count::<Laser>(&game), 0);
+    assert_eq!(count::<Pooled>(&game), 1);
+}
+
+#[test]
+fn ship_is_constrained_to_arena() {
+    let mut game = game_without_spawner();
+    set_player_input(&mut game, PlayerInput::RIGHT | PlayerInput::UP);
+    game.step(300);
+    let translation = ship_translation(&game);
+    assert_eq!(translation.x(), (ARENA_WIDTH - 16.) / 2.);
+    assert_eq!(translation.y(), (ARENA_HEIGHT - 24.) / 2.);
+
+    set_player_input(&mut game, PlayerInput::LEFT | PlayerInput::DOWN);
+    game.step(300);
+    let translation = ship_translation(&game);
+    assert_eq!(translation.x(), -(ARENA_WIDTH - 16.) / 2.);
+    assert_eq!(translation.y(), -(ARENA_HEIGHT - 24.) / 2.);
+}
+
+#[test]
+fn ship_is_constrained_to_the_arena_of_the_config() {
+    let mut config = GameConfig::default();
+    config.arena = (300., 200.);
+    let mut game = game_without_spawner_with_config(config);
+    set_player_input(&mut game, PlayerInput::RIGHT | PlayerInput::UP);
+    game.step(300);
+    let translation = ship_translation(&game);
+    assert_eq!(translation.x(), (300. - 16.) / 2.);
+    assert_eq!(translation.y(), (200. - 24.) / 2.);
+}
+
+#[test]
+fn arena_is_shown_with_a_whole_number_of_pixels_per_unit() {
+    let arena = Arena::new(ARENA_WIDTH, ARENA_HEIGHT);
+    assert_eq!(arena.pixel_scale(Vec2::new(800., 600.)), 2.);
+    assert_eq!(arena.pixel_scale(Vec2::new(1920., 1080.)), 4.);
+    assert_eq!(arena.pixel_scale(Vec2::new(ARENA_WIDTH, ARENA_HEIGHT)), 1.);
+    // Too small for one pixel per unit, the arena is shrunk to fit
+    assert_eq!(arena.pixel_scale(Vec2::new(90., 240.)), 0.5);
+}
+
+#[test]
+fn ship_banking_follows_transition_table() {
+    use ShipAnimationState::*;
+
+    let mut game = game_without_spawner();
+    assert_eq!(
+        bank_states(&mut game, PlayerInput::LEFT, 60),
+        vec![Stabilized, HalfLeft, FullLeft]
+    );
+    assert_eq!(
+        bank_states(&mut game, 0, 60),
+        vec![FullLeft, HalfLeft, Stabilized]
+    );
+    assert_eq!(
+        bank_states(&mut game, PlayerInput::RIGHT, 60),
+        vec![Stabilized, HalfRight, FullRight]
+    );
+    assert_eq!(
+        bank_states(&mut game, PlayerInput::LEFT, 60),
+        vec![FullRight, HalfRight, Stabilized, HalfLeft, FullLeft]
+    );
+}
+
+#[test]
+fn ship_bank_clip_is_taken_from_the_animation_manifest() {
+    let mut game = game_without_spawner();
+    bank_states(&mut game, PlayerInput::RIGHT, 60);
+    let clips: Vec<(String, String)> = game
+        .app
+        .world
+        .query::<(&Ship, &LayeredAnimation)>()
+        .flat_map(|(_, layered_animation)| {
+            layered_animation
+                .channels
+                .iter()
+                .map(|channel| (channel.name.clone(), channel.animation.clip.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(
+        clips,
+        vec![
+            ("bank".to_string(), "ship_bank_full_right".to_string()),
+            ("flicker".to_string(), "ship_flicker".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn enemy_leaving_bottom_is_despawned_with_its_weapon() {
+    let mut game = game_without_spawner();
+    // A big enemy has a weapon, it goes past the bottom of the arena after a few ticks
+    spawn_enemy(
+        &mut game,
+        EnemyVariant::Big,
+        Vec3::new(60., -ARENA_HEIGHT / 2. - 15., 0.),
+    );
+
+    game.step(1);
+    assert_eq!(count::<Enemy>(&game), 1);
+    assert_eq!(count::<Weapon>(&game), 2);
+
+    game.step(10);
+    assert_eq!(count::<Enemy>(&game), 0);
+    assert_eq!(count::<Weapon>(&game), 1);
+}
+
+#[test]
+fn enemy_coming_in_from_the_top_is_kept() {
+    let mut game = game_without_spawner();
+    spawn_enemy(
+        &mut game,
+        EnemyVariant::Big,
+        Vec3::new(0., (ARENA_HEIGHT + 32.) / 2., 0.),
+    );
+    game.step(10);
+    assert_eq!(count::<Enemy>(&game), 1);
+}
+
+#[test]
+fn laser_is_removed_as_soon_as_it_leaves_the_arena() {
+    let mut game = game_without_spawner();
+    // Two units per tick, it is out once its bottom is past the top edge
+    spawn_moving_laser(
+        &mut game,
+        Vec3::new(0., ARENA_HEIGHT / 2. - 5., 0.),
+        Vec2::new(0., 120.),
+        ArenaBounds::new(BoundsPolicy::Despawn, 0.),
+    );
+    game.step(2);
+    assert_eq!(count::<Laser>(&game), 1);
+    game.step(2);
+    assert_eq!(count::<Laser>(&game), 0);
+    assert_eq!(count::<Pooled>(&game), 1);
+}
+
+#[test]
+fn bouncing_laser_ricochets_off_the_edge() {
+    let mut game = game_without_spawner();
+    spawn_moving_laser(
+        &mut game,
+        Vec3::new(0., 100., 0.),
+        Vec2::new(0., 120.),
+        ArenaBounds::new(BoundsPolicy::Bounce, 0.),
+    );
+    game.step(30);
+    let (translation, velocity) = laser_motion(&game);
+    assert_eq!(velocity, Vec2::new(0., -120.));
+    assert!(translation.y() < ARENA_HEIGHT / 2. - 2.);
+}
+
+#[test]
+fn wrapping_laser_comes_back_from_the_opposite_edge() {
+    let mut game = game_without_spawner();
+    spawn_moving_laser(
+        &mut game,
+        Vec3::new(ARENA_WIDTH / 2. - 1., 0., 0.),
+        Vec2::new(120., 0.),
+        ArenaBounds::new(BoundsPolicy::Wrap, 0.),
+    );
+    game.step(2);
+    let (translation, velocity) = laser_motion(&game);
+    assert!(translation.x() < -ARENA_WIDTH / 2.);
+    assert_eq!(velocity, Vec2::new(120., 0.));
+    // It moved on from the other side instead of going across the arena
+    let previous_translation = game
+        .app
+        .world
+        .query::<(&Laser, &PreviousTranslation)>()
+        .map(|(_, previous_translation)| previous_translation.0)
+        .next()
+        .expect("Could not find laser");
+    assert!(previous_translation.x() < translation.x());
+}
+
+#[test]
+fn second_player_joins_by_pressing_fire() {
+    let mut game = game_without_spawner();
+    game.step(10);
+    assert_eq!(count::<Ship>(&game), 1);
+    assert_eq!(score_and_lives(&game, Player(1)), None);
+
+    set_input_of(&mut game, Player(1), PlayerInput::FIRE);
+    game.step(1);
+    assert_eq!(count::<Ship>(&game), 2);
+    assert!(player_ship(&game, Player(1)).is_some());
+    assert_eq!(score_and_lives(&game, Player(1)), Some((0, 1)));
+}
+
+#[test]
+fn players_can_not_hit_each_other() {
+    let mut game = game_with_two_players(GameConfig::default());
+    let second_ship = player_ship(&game, Player(1)).expect("Could not find second ship");
+    spawn_laser(
+        &mut game,
+        second_ship,
+        ship_translation(&game),
+        CollisionLayer::ship_laser(),
+    );
+
+    game.step(10);
+    assert_eq!(count::<Ship>(&game), 2);
+    assert!(!app_exit_sent(&game));
+}
+
+#[test]
+fn destroyed_enemy_is_credited_to_the_player_who_fired() {
+    let mut game = game_with_two_players(GameConfig::default());
+    let second_ship = player_ship(&game, Player(1)).expect("Could not find second ship");
+    // Away from both ships' line of fire
+    let enemy_translation = Vec3::new(-60., 60., 0.);
+    spawn_enemy(&mut game, EnemyVariant::Medium, enemy_translation);
+    spawn_laser(
+        &mut game,
+        second_ship,
+        enemy_translation,
+        CollisionLayer::ship_laser(),
+    );
+
+    game.step(2);
+    assert_eq!(count::<Enemy>(&game), 0);
+    let config = GameConfig::default();
+    assert_eq!(
+        score_and_lives(&game, Player(1)),
+        Some((config.enemy.scores.medium, 1))
+    );
+    assert_eq!(score_and_lives(&game, Player(0)), Some((0, 1)));
+}
+
+#[test]
+fn game_ends_only_when_every_player_is_out() {
+    let mut game = game_with_two_players(GameConfig::default());
+    let other_source = game.app.world.spawn((Transform::default(),));
+    let first_ship = player_ship(&game, Player(0)).expect("Could not find first ship");
+    let first_translation = game
+        .app
+        .world
+        .get::<Transform>(first_ship)
+        .expect("Could not get first ship transform")
+        .translation;
+    spawn_laser(
+        &mut game,
+        other_source,
+        first_translation,
+        CollisionLayer::enemy_laser(),
+    );
+
+    game.step(2);
+    assert!(player_ship(&game, Player(0)).is_none());
+    assert!(player_ship(&game, Player(1)).is_some());
+    assert!(!app_exit_sent(&game));
+
+    let second_ship = player_ship(&game, Player(1)).expect("Could not find second ship");
+    let second_translation = game
+        .app
+        .world
+        .get::<Transform>(second_ship)
+        .expect("Could not get second ship transform")
+        .translation;
+    spawn_laser(
+        &mut game,
+        other_source,
+        second_translation,
+        CollisionLayer::enemy_laser(),
+    );
+
+    game.step(2);
+    assert_eq!(count::<Ship>(&game), 0);
+    assert!(app_exit_sent(&game));
+}
+
+#[test]
+fn ship_comes_back_after_the_respawn_delay_while_lives_are_left() {
+    let mut config = GameConfig::default();
+    config.ship.lives = 2;
+    config.ship.respawn_delay = 0.5;
+    let mut game = game_without_spawner_with_config(config);
+    let other_source = game.app.world.spawn((Transform::default(),));
+    spawn_laser(
+        &mut game,
+        other_source,
+        ship_translation(&game),
+        CollisionLayer::enemy_laser(),
+    );
+
+    game.step(2);
+    assert_eq!(count::<Ship>(&game), 0);
+    assert_eq!(score_and_lives(&game, Player(0)), Some((0, 1)));
+    assert!(!app_exit_sent(&game));
+
+    game.step(60);
+    assert_eq!(count::<Ship>(&game), 1);
+}
@@ -0,0 +1,384 @@
+use bevy::{
+    input::{
+        gamepad::{
+            Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadEvent,
+            GamepadEventType,
+        },
+        keyboard::KeyCode,
+        mouse::MouseButton,
+        Axis, Input,
+    },
+    prelude::*,
+};
+use bevy_spaceshooter::{
+    components::{
+        ArenaBounds, BoundsPolicy, CollisionLayer, EnemySpawner, HitBox, Laser, Player, Ship,
+        Velocity,
+    },
+    config::GameConfig,
+    constant::{ANIMATION_CLIP_ENEMY_LASER, ARENA_WIDTH},
+    events::{ReportEvent, SpawnLaserEvent},
+    game::HeadlessGame,
+    input::InputBindings,
+    resource::{
+        ConnectedGamepads, FixedTimestep, GameSettings, PlayerInput, PlayerInputs, Players,
+        Pointer, Rebinding,
+    },
+};
+use std::time::Duration;
+
+/// A game with only the ship in it, the ship is controlled by the devices
+fn game_without_spawner() -> HeadlessGame {
+    let mut game = HeadlessGame::new(GameConfig::default(), 0);
+    let spawners: Vec<Entity> = game
+        .app
+        .world
+        .query::<(Entity, &EnemySpawner)>()
+        .map(|(entity, _)| entity)
+        .collect();
+    for spawner in spawners {
+        game.app
+            .world
+            .despawn(spawner)
+            .expect("Could not despawn enemy spawner");
+    }
+    game
+}
+
+fn ship_position(game: &HeadlessGame) -> Vec2 {
+    game.app
+        .world
+        .query::<(&Ship, &Transform)>()
+        .map(|(_, transform)| Vec2::new(transform.translation.x(), transform.translation.y()))
+        .next()
+        .expect("Could not find ship")
+}
+
+fn ship_velocity(game: &HeadlessGame) -> Vec2 {
+    game.app
+        .world
+        .query::<(&Ship, &Velocity)>()
+        .map(|(_, velocity)| velocity.0)
+        .next()
+        .expect("Could not find ship")
+}
+
+fn player_input(game: &HeadlessGame) -> PlayerInput {
+    game.app
+        .resources
+        .get::<PlayerInputs>()
+        .expect("Could not get player inputs")
+        .get(Player(0))
+}
+
+fn send_gamepad_event(game: &mut HeadlessGame, gamepad: Gamepad, event_type: GamepadEventType) {
+    game.app
+        .resources
+        .get_mut::<Events<GamepadEvent>>()
+        .expect("Could not get gamepad events")
+        .send(GamepadEvent(gamepad, event_type));
+}
+
+fn tilt_left_stick(game: &mut HeadlessGame, gamepad: Gamepad, x: f32, y: f32) {
+    let mut gamepad_axes = game
+        .app
+        .resources
+        .get_mut::<Axis<GamepadAxis>>()
+        .expect("Could not get gamepad axes");
+    gamepad_axes.set(GamepadAxis(gamepad, GamepadAxisType::LeftStickX), x);
+    gamepad_axes.set(GamepadAxis(gamepad, GamepadAxisType::LeftStickY), y);
+}
+
+fn press_button(game: &mut HeadlessGame, gamepad: Gamepad, button_type: GamepadButtonType) {
+    game.app
+        .resources
+        .get_mut::<Input<GamepadButton>>()
+        .expect("Could not get gamepad buttons")
+        .press(GamepadButton(gamepad, button_type));
+}
+
+#[test]
+fn stick_moves_the_ship_proportionally_outside_the_deadzone() {
+    let mut game = game_without_spawner();
+    let move_speed = GameConfig::default().ship.move_speed;
+    let gamepad = Gamepad(0);
+    send_gamepad_event(&mut game, gamepad, GamepadEventType::Connected);
+
+    tilt_left_stick(&mut game, gamepad, 0.1, 0.);
+    game.step(1);
+    assert_eq!(ship_velocity(&game), Vec2::zero());
+
+    tilt_left_stick(&mut game, gamepad, 0.6, 0.);
+    game.step(1);
+    let half_tilt_speed = ship_velocity(&game).x();
+    assert!(half_tilt_speed > 0.25 * move_speed && half_tilt_speed < 0.75 * move_speed);
+
+    tilt_left_stick(&mut game, gamepad, 1., 1.);
+    game.step(1);
+    let velocity = ship_velocity(&game);
+    assert!((velocity.length() - move_speed).abs() < 0.01 * move_speed);
+    assert!(velocity.x() > 0. && velocity.y() > 0.);
+}
+
+/// The number of lasers that were fired by the players and by the enemies
+fn laser_counts(game: &HeadlessGame) -> (usize, usize) {
+    let lasers: Vec<bool> = game
+        .app
+        .world
+        .query::<&Laser>()
+        .map(|laser| laser.player.is_some())
+        .collect();
+    let fired_by_players = lasers.iter().filter(|by_player| **by_player).count();
+    (fired_by_players, lasers.len() - fired_by_players)
+}
+
+fn bombs(game: &HeadlessGame) -> u32 {
+    game.app
+        .resources
+        .get::<Players>()
+        .expect("Could not get players")
+        .0[0]
+        .as_ref()
+        .expect("Could not get the first player")
+        .bombs
+}
+
+/// Add an enemy laser that stands still above the ship
+fn spawn_enemy_laser(game: &mut HeadlessGame) {
+    let source = game.app.world.spawn((Transform::default(),));
+    game.app
+        .resources
+        .get_mut::<Events<SpawnLaserEvent>>()
+        .expect("Could not get spawn laser events")
+        .send(SpawnLaserEvent {
+            laser_source: source,
+            laser_translation: Vec3::new(-50., 100., 0.),
+            laser_velocity: Velocity(Vec2::zero()),
+            laser_hit_box: HitBox(Vec2::new(4., 4.)),
+            laser_time_to_live_duration: Duration::from_secs(10),
+            laser_animation_clip: ANIMATION_CLIP_ENEMY_LASER.to_string(),
+            laser_collision_layer: CollisionLayer::enemy_laser(),
+            laser_bounds: ArenaBounds::new(BoundsPolicy::Despawn, 0.),
+        });
+}
+
+#[test]
+fn face_buttons_fire_and_bomb() {
+    let mut game = game_without_spawner();
+    let gamepad = Gamepad(0);
+    send_gamepad_event(&mut game, gamepad, GamepadEventType::Connected);
+    spawn_enemy_laser(&mut game);
+    game.step(1);
+    assert_eq!(laser_counts(&game), (0, 1));
+    let bombs_at_start = bombs(&game);
+
+    press_button(&mut game, gamepad, GamepadButtonType::South);
+    game.step(1);
+    assert!(player_input(&game).pressed(PlayerInput::FIRE));
+    let fired = (0..60).any(|_| {
+        game.step(1);
+        laser_counts(&game).0 > 0
+    });
+    assert!(fired);
+    assert_eq!(laser_counts(&game).1, 1);
+
+    press_button(&mut game, gamepad, GamepadButtonType::East);
+    game.step(2);
+    assert!(player_input(&game).pressed(PlayerInput::BOMB));
+    assert_eq!(laser_counts(&game).1, 0);
+    assert_eq!(bombs(&game), bombs_at_start - 1);
+}
+
+#[test]
+fn gamepad_is_ignored_until_it_is_connected() {
+    let mut game = game_without_spawner();
+    let gamepad = Gamepad(0);
+
+    tilt_left_stick(&mut game, gamepad, 1., 0.);
+    press_button(&mut game, gamepad, GamepadButtonType::South);
+    game.step(1);
+    assert_eq!(player_input(&game), PlayerInput::default());
+
+    send_gamepad_event(&mut game, gamepad, GamepadEventType::Connected);
+    game.step(1);
+    assert!(ship_velocity(&game).x() > 0.);
+}
+
+fn gamepad_of(game: &HeadlessGame, player: Player) -> Option<Gamepad> {
+    game.app
+        .resources
+        .get::<ConnectedGamepads>()
+        .expect("Could not get connected gamepads")
+        .gamepad(player)
+}
+
+/// Run a step and get what the game reported during it
+fn step_reports(game: &mut HeadlessGame) -> Vec<ReportEvent> {
+    let mut report_reader = game
+        .app
+        .resources
+        .get::<Events<ReportEvent>>()
+        .expect("Could not get report events")
+        .get_reader_current();
+    game.step(1);
+    let report_events = game
+        .app
+        .resources
+        .get::<Events<ReportEvent>>()
+        .expect("Could not get report events");
+    report_reader.iter(&report_events).cloned().collect()
+}
+
+#[test]
+fn unplugging_the_active_gamepad_pauses_until_it_is_plugged_back_in() {
+    let mut game = game_without_spawner();
+    let first_gamepad = Gamepad(0);
+    let second_gamepad = Gamepad(1);
+    send_gamepad_event(&mut game, first_gamepad, GamepadEventType::Connected);
+    send_gamepad_event(&mut game, second_gamepad, GamepadEventType::Connected);
+    tilt_left_stick(&mut game, first_gamepad, -1., 0.);
+    tilt_left_stick(&mut game, second_gamepad, 1., 0.);
+    game.step(1);
+    assert!(ship_velocity(&game).x() < 0.);
+
+    send_gamepad_event(&mut game, first_gamepad, GamepadEventType::Disconnected);
+    assert_eq!(
+        step_reports(&mut game),
+        vec![
+            ReportEvent::GamepadDisconnected(first_gamepad),
+            ReportEvent::Paused
+        ]
+    );
+    assert_eq!(gamepad_of(&game, Player(0)), None);
+    assert_eq!(gamepad_of(&game, Player(1)), Some(second_gamepad));
+    assert!(
+        game.app
+            .resources
+            .get::<FixedTimestep>()
+            .expect("Could not get fixed timestep")
+            .paused
+    );
+
+    // The second player's gamepad does not take over the first player's ship
+    game.app
+        .resources
+        .get_mut::<FixedTimestep>()
+        .expect("Could not get fixed timestep")
+        .paused = false;
+    game.step(1);
+    assert_eq!(ship_velocity(&game), Vec2::zero());
+
+    send_gamepad_event(&mut game, first_gamepad, GamepadEventType::Connected);
+    assert_eq!(
+        step_reports(&mut game),
+        vec![ReportEvent::GamepadConnected {
+            gamepad: first_gamepad,
+            player: Player(0)
+        }]
+    );
+    assert_eq!(gamepad_of(&game, Player(0)), Some(first_gamepad));
+    assert!(ship_velocity(&game).x() < 0.);
+}
+
+#[test]
+fn new_gamepad_takes_the_empty_slot() {
+    let mut connected_gamepads = ConnectedGamepads::default();
+    assert_eq!(connected_gamepads.connect(Gamepad(0)), Some(Player(0)));
+    assert_eq!(connected_gamepads.connect(Gamepad(1)), Some(Player(1)));
+    assert_eq!(connected_gamepads.connect(Gamepad(1)), Some(Player(1)));
+
+    assert_eq!(connected_gamepads.disconnect(Gamepad(0)), Some(Player(0)));
+    assert_eq!(connected_gamepads.disconnect(Gamepad(0)), None);
+    assert_eq!(connected_gamepads.connect(Gamepad(2)), Some(Player(0)));
+    // Every slot is taken, so the first gamepad is left out when it comes back
+    assert_eq!(connected_gamepads.connect(Gamepad(0)), None);
+
+    // A gamepad that comes back gets its own slot back, even when another slot is empty
+    connected_gamepads.disconnect(Gamepad(2));
+    connected_gamepads.disconnect(Gamepad(1));
+    assert_eq!(connected_gamepads.connect(Gamepad(1)), Some(Player(1)));
+    assert_eq!(connected_gamepads.gamepad(Player(0)), None);
+}
+
+/// A game whose ship follows the pointer, with the cursor at the given arena position
+fn game_with_pointer_control(position: Vec2) -> HeadlessGame {
+    let mut game = game_without_spawner();
+    game.app
+        .resources
+        .get_mut::<GameSettings>()
+        .expect("Could not get game settings")
+        .pointer_control = true;
+    game.app
+        .resources
+        .get_mut::<Pointer>()
+        .expect("Could not get pointer")
+        .position = Some(position);
+    game
+}
+
+#[test]
+fn ship_follows_the_pointer_without_going_over_its_speed() {
+    let target = Vec2::new(40., 60.);
+    let mut game = game_with_pointer_control(target);
+    let move_speed = GameConfig::default().ship.move_speed;
+
+    let mut distance = (target - ship_position(&game)).length();
+    for _ in 0..120 {
+        game.step(1);
+        assert!(ship_velocity(&game).length() <= move_speed * 1.001);
+        let new_distance = (target - ship_position(&game)).length();
+        assert!(new_distance <= distance);
+        distance = new_distance;
+    }
+    assert!(distance < 1.);
+}
+
+#[test]
+fn ship_following_the_pointer_stays_in_the_arena() {
+    let mut game = game_with_pointer_control(Vec2::new(1000., 0.));
+    game.step(240);
+
+    let position = ship_position(&game);
+    assert!(position.x() > 0. && position.x() <= ARENA_WIDTH / 2.);
+}
+
+#[test]
+fn mouse_buttons_fire_and_bomb_when_following_the_pointer() {
+    let mut game = game_with_pointer_control(Vec2::zero());
+    {
+        let mut mouse_input = game
+            .app
+            .resources
+            .get_mut::<Input<MouseButton>>()
+            .expect("Could not get mouse buttons");
+        mouse_input.press(MouseButton::Left);
+        mouse_input.press(MouseButton::Right);
+    }
+    game.step(1);
+    assert!(player_input(&game).pressed(PlayerInput::FIRE));
+    assert!(player_input(&game).pressed(PlayerInput::BOMB));
+}
+
+#[test]
+fn rebinding_screen_shows_the_keys_and_the_last_message() {
+    assert!(Rebinding::Inactive
+        .screen_lines()
+        .iter()
+        .all(|line| line.is_empty()));
+
+    let rebinding = Rebinding::Active {
+        player: Player(1),
+        action_idx: 1,
+        keys: vec![KeyCode::N],
+        conflicting_key: None,
+        bindings: InputBindings::default(),
+        was_paused: false,
+        message: "N added to move right".to_string(),
+    };
+    let lines = rebinding.screen_lines();
+    assert_eq!(lines.len(), Rebinding::LINE_COUNT);
+    assert!(lines[0].starts_with("Player 2"));
+    assert_eq!(lines[1].trim_end(), "  move left   J");
+    assert_eq!(lines[2].trim_end(), "> move right  N");
+    assert_eq!(lines[Rebinding::LINE_COUNT - 1], "N added to move right");
+}
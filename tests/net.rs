@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+use bevy_spaceshooter::{
+    components::{Enemy, Laser, Player, Ship},
+    config::GameConfig,
+    game::HeadlessGame,
+    net::{NetConditions, NetError, NetSession, Packet},
+    resource::{FixedTimestep, InputSource, PlayerInput, Players},
+    snapshot::Snapshot,
+};
+use std::{
+    net::UdpSocket,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// What the players can see of the game world, entities are sorted by where they are so the
+/// fingerprints of two games can be compared even when their entities are numbered differently
+#[derive(Debug, PartialEq)]
+struct Fingerprint {
+    ships: Vec<(usize, Vec3)>,
+    enemies: Vec<Vec3>,
+    lasers: Vec<Vec3>,
+    players: Vec<Option<(u32, u32)>>,
+}
+
+fn fingerprint(game: &HeadlessGame) -> Fingerprint {
+    let sorted = |mut translations: Vec<Vec3>| {
+        translations.sort_by(|a, b| {
+            (a.x(), a.y())
+                .partial_cmp(&(b.x(), b.y()))
+                .expect("Could not compare translations")
+        });
+        translations
+    };
+    let mut ships: Vec<(usize, Vec3)> = game
+        .app
+        .world
+        .query::<(&Ship, &Player, &Transform)>()
+        .map(|(_, player, transform)| (player.0, transform.translation))
+        .collect();
+    ships.sort_by_key(|(player, _)| *player);
+    Fingerprint {
+        ships,
+        enemies: sorted(
+            game.app
+                .world
+                .query::<(&Enemy, &Transform)>()
+                .map(|(_, transform)| transform.translation)
+                .collect(),
+        ),
+        lasers: sorted(
+            game.app
+                .world
+                .query::<(&Laser, &Transform)>()
+                .map(|(_, transform)| transform.translation)
+                .collect(),
+        ),
+        players: game
+            .app
+            .resources
+            .get::<Players>()
+            .expect("Could not get players")
+            .0
+            .iter()
+            .map(|player_state| {
+                player_state
+                    .as_ref()
+                    .map(|player_state| (player_state.score, player_state.lives))
+            })
+            .collect(),
+    }
+}
+
+/// The session's current tick and the number of ticks that are confirmed
+fn session_ticks(game: &HeadlessGame) -> (usize, usize) {
+    match &*game
+        .app
+        .resources
+        .get::<InputSource>()
+        .expect("Could not get input source")
+    {
+        InputSource::Network(session) => (session.tick(), session.confirmed_ticks()),
+        _ => panic!("Could not get network session"),
+    }
+}
+
+fn press_keys(game: &mut HeadlessGame, keys: &[KeyCode]) {
+    let mut keyboard_input = game
+        .app
+        .resources
+        .get_mut::<Input<KeyCode>>()
+        .expect("Could not get keyboard input");
+    for key in [
+        KeyCode::A,
+        KeyCode::D,
+        KeyCode::W,
+        KeyCode::S,
+        KeyCode::Space,
+    ]
+    .iter()
+    {
+        if keys.contains(key) {
+            keyboard_input.press(*key);
+        } else {
+            keyboard_input.release(*key);
+        }
+    }
+}
+
+/// Two sessions that are connected to each other over the loopback interface
+fn connected_sessions(conditions: NetConditions) -> (NetSession, NetSession) {
+    let port = UdpSocket::bind("127.0.0.1:0")
+        .and_then(|socket| socket.local_addr())
+        .expect("Could not find a free port")
+        .port();
+    let connected = Arc::new(AtomicBool::new(false));
+    let host = {
+        let connected = connected.clone();
+        thread::spawn(move || {
+            let mut session =
+                NetSession::host(port, 7, conditions).expect("Could not host the game");
+            // The welcome can be held back or lost, it is sent again until the other game is in
+            while !connected.load(Ordering::SeqCst) {
+                session.poll();
+                thread::sleep(Duration::from_millis(1));
+            }
+            session
+        })
+    };
+    let client = NetSession::connect(&format!("127.0.0.1:{}", port), conditions)
+        .expect("Could not join the game");
+    connected.store(true, Ordering::SeqCst);
+    (host.join().expect("Could not join host thread"), client)
+}
+
+#[test]
+fn packets_are_decoded_as_they_were_encoded() {
+    let packets = vec![
+        Packet::Join,
+        Packet::Welcome { seed: u64::MAX },
+        Packet::Inputs {
+            received: 12,
+            start_tick: 40,
+            inputs: vec![
+                PlayerInput::from_bits(PlayerInput::LEFT | PlayerInput::FIRE),
+                PlayerInput::from_raw(PlayerInput::BOMB, [-127, 64]),
+            ],
+        },
+    ];
+    for packet in packets {
+        assert_eq!(
+            Packet::decode(&packet.encode()).expect("Could not decode packet"),
+            packet
+        );
+    }
+}
+
+#[test]
+fn packets_from_something_else_are_rejected() {
+    let mut wrong_magic = Packet::Join.encode();
+    wrong_magic[0] ^= 0xff;
+    assert!(matches!(
+        Packet::decode(&wrong_magic),
+        Err(NetError::NotAPacket)
+    ));
+
+    let mut wrong_version = Packet::Join.encode();
+    wrong_version[4] ^= 0xff;
+    assert!(matches!(
+        Packet::decode(&wrong_version),
+        Err(NetError::VersionMismatch { .. })
+    ));
+
+    let mut truncated = Packet::Welcome { seed: 1 }.encode();
+    truncated.pop();
+    assert!(matches!(
+        Packet::decode(&truncated),
+        Err(NetError::NotAPacket)
+    ));
+}
+
+#[test]
+fn restored_snapshot_runs_the_same_ticks_again() {
+    let mut game = HeadlessGame::new(GameConfig::default(), 3);
+    game.step(100);
+    let snapshot = Snapshot::take(&game.app.world, &mut game.app.resources);
+
+    game.step(120);
+    let first_run = fingerprint(&game);
+    snapshot.restore(&mut game.app.world, &mut game.app.resources);
+    game.step(120);
+    assert_eq!(fingerprint(&game), first_run);
+}
+
+#[test]
+fn networked_games_agree_despite_latency_and_packet_loss() {
+    const TICKS: usize = 300;
+
+    let (host, client) = connected_sessions(NetConditions {
+        latency: Duration::from_millis(20),
+        packet_loss: 0.1,
+    });
+    let mut games = [
+        HeadlessGame::networked(GameConfig::default(), host),
+        HeadlessGame::networked(GameConfig::default(), client),
+    ];
+
+    let mut frame = 0;
+    while games.iter().any(|game| session_ticks(game).1 < TICKS) {
+        for (idx, game) in games.iter_mut().enumerate() {
+            // The players change direction at different times so the guesses are often wrong
+            let moving_right = (frame / (20 + 7 * idx)) % 2 == 0;
+            if moving_right {
+                press_keys(game, &[KeyCode::D, KeyCode::Space]);
+            } else {
+                press_keys(game, &[KeyCode::A]);
+            }
+            // Both games stop at the same tick, they keep talking until it is confirmed
+            let reached_end = session_ticks(game).0 >= TICKS;
+            game.app
+                .resources
+                .get_mut::<FixedTimestep>()
+                .expect("Could not get fixed timestep")
+                .paused = reached_end;
+            game.step(1);
+        }
+        frame += 1;
+        assert!(frame < 100_000, "The games did not confirm their ticks");
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(session_ticks(&games[0]).0, TICKS);
+    assert_eq!(session_ticks(&games[1]).0, TICKS);
+    assert_eq!(fingerprint(&games[0]), fingerprint(&games[1]));
+}
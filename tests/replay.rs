@@ -0,0 +1,190 @@
+use bevy::{input::keyboard::KeyCode, prelude::*};
+use bevy_spaceshooter::{
+    config::GameConfig,
+    constant::{REPLAY_MAGIC, REPLAY_VERSION},
+    game::HeadlessGame,
+    replay::{Replay, ReplayError},
+    resource::{GameSettings, InputSource, PlayerInput, PlayerInputs, Players},
+};
+use std::path::PathBuf;
+
+/// A replay whose inputs change every few ticks, with some stick tilt in them
+fn sample_replay() -> Replay {
+    let mut replay = Replay::new(0xDEAD_BEEF, 2, true);
+    for tick in 0..100 {
+        let mut inputs = PlayerInputs::default();
+        inputs.0[0] = PlayerInput::from_bits(if tick < 40 {
+            PlayerInput::LEFT
+        } else {
+            PlayerInput::RIGHT | PlayerInput::FOCUS
+        });
+        inputs.0[1].set_stick(Vec2::new((tick / 10) as f32 / 10., -0.5));
+        replay.inputs.push(inputs);
+    }
+    replay
+}
+
+#[test]
+fn decoding_an_encoded_replay_gives_it_back() {
+    let replay = sample_replay();
+    let bytes = replay.encode();
+    assert_eq!(
+        Replay::decode(&bytes).expect("Could not decode replay"),
+        replay
+    );
+
+    let empty = Replay::new(1, 1, false);
+    assert_eq!(
+        Replay::decode(&empty.encode()).expect("Could not decode replay"),
+        empty
+    );
+}
+
+#[test]
+fn held_inputs_are_stored_once() {
+    let mut replay = Replay::new(1, 1, false);
+    replay.inputs = vec![PlayerInputs::default(); 10_000];
+    let mut changing = replay.clone();
+    changing.inputs[5000].0[0] = PlayerInput::from_bits(PlayerInput::UP);
+    assert!(replay.encode().len() < changing.encode().len());
+    assert!(changing.encode().len() < 100);
+}
+
+#[test]
+fn file_without_the_magic_number_is_not_a_replay() {
+    let mut bytes = sample_replay().encode();
+    bytes[0] = b'X';
+    assert!(matches!(
+        Replay::decode(&bytes),
+        Err(ReplayError::NotAReplay)
+    ));
+    assert!(matches!(
+        Replay::decode(b"SS"),
+        Err(ReplayError::NotAReplay)
+    ));
+}
+
+#[test]
+fn truncated_replay_is_rejected() {
+    let bytes = sample_replay().encode();
+    for length in [REPLAY_MAGIC.len() + 1, 10, bytes.len() - 1].iter() {
+        assert!(matches!(
+            Replay::decode(&bytes[..*length]),
+            Err(ReplayError::Truncated)
+        ));
+    }
+
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert!(matches!(
+        Replay::decode(&trailing),
+        Err(ReplayError::TrailingBytes)
+    ));
+}
+
+#[test]
+fn replay_of_another_version_is_rejected() {
+    let mut bytes = sample_replay().encode();
+    let version_start = REPLAY_MAGIC.len();
+    bytes[version_start..version_start + 2].copy_from_slice(&(REPLAY_VERSION + 1).to_le_bytes());
+    match Replay::decode(&bytes) {
+        Err(ReplayError::VersionMismatch { found, expected }) => {
+            assert_eq!(found, REPLAY_VERSION + 1);
+            assert_eq!(expected, REPLAY_VERSION);
+        }
+        other => panic!("Expected a version mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn replay_with_invalid_settings_is_rejected() {
+    let players_idx = REPLAY_MAGIC.len() + 2 + 8;
+    for (idx, value) in [(players_idx, 0), (players_idx, 3), (players_idx + 1, 2)].iter() {
+        let mut bytes = sample_replay().encode();
+        bytes[*idx] = *value;
+        assert!(matches!(
+            Replay::decode(&bytes),
+            Err(ReplayError::InvalidHeader)
+        ));
+    }
+}
+
+/// Where every entity is and the players' state
+fn game_state(game: &HeadlessGame) -> (Vec<(Entity, Vec3)>, String) {
+    let mut translations: Vec<(Entity, Vec3)> = game
+        .app
+        .world
+        .query::<(Entity, &Transform)>()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+    translations.sort_by_key(|(entity, _)| *entity);
+    let players = game
+        .app
+        .resources
+        .get::<Players>()
+        .expect("Could not get players");
+    (translations, format!("{:?}", *players))
+}
+
+/// Hold the keys of the given tick, both ships weave from side to side and fire now and then
+fn press_scripted_keys(game: &mut HeadlessGame, tick: u32) {
+    let mut keyboard_input = game
+        .app
+        .resources
+        .get_mut::<Input<KeyCode>>()
+        .expect("Could not get keyboard input");
+    let keys = [
+        (KeyCode::A, (tick / 40) % 2 == 0),
+        (KeyCode::D, (tick / 40) % 2 == 1),
+        (KeyCode::L, (tick / 25) % 2 == 0),
+        (KeyCode::J, (tick / 25) % 2 == 1),
+        (KeyCode::Space, tick % 60 < 30),
+        (KeyCode::U, tick % 90 < 20),
+    ];
+    for (key, held) in keys.iter() {
+        if *held {
+            keyboard_input.press(*key);
+        } else {
+            keyboard_input.release(*key);
+        }
+    }
+}
+
+#[test]
+fn playing_a_recorded_run_back_ends_in_the_same_state() {
+    let ticks = 600;
+    let mut recording = HeadlessGame::recording(
+        GameConfig::default(),
+        Replay::new(11, 2, true),
+        PathBuf::from("unused.replay"),
+    );
+    for tick in 0..ticks {
+        press_scripted_keys(&mut recording, tick);
+        recording.step(1);
+    }
+    let replay = match &*recording
+        .app
+        .resources
+        .get::<InputSource>()
+        .expect("Could not get input source")
+    {
+        InputSource::Recording { replay, .. } => replay.clone(),
+        _ => panic!("Expected the game to be recording"),
+    };
+    assert_eq!(replay.inputs.len(), ticks as usize);
+
+    // The settings come from the replay, not from the defaults that the game is started with
+    let replay = Replay::decode(&replay.encode()).expect("Could not decode replay");
+    let mut playback = HeadlessGame::playback(GameConfig::default(), replay);
+    {
+        let settings = playback
+            .app
+            .resources
+            .get::<GameSettings>()
+            .expect("Could not get game settings");
+        assert_eq!(settings.players, 2);
+        assert!(settings.god_mode);
+    }
+    playback.step(ticks);
+    assert_eq!(game_state(&playback), game_state(&recording));
+}
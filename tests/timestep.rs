@@ -0,0 +1,66 @@
+use bevy_spaceshooter::resource::FixedTimestep;
+
+/// A timestep that follows the frames' time, with ticks of a tenth of a second
+fn timestep() -> FixedTimestep {
+    FixedTimestep {
+        step_seconds: 0.1,
+        max_steps_per_frame: 5,
+        real_time: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn frame_shorter_than_a_step_runs_no_tick() {
+    let mut timestep = timestep();
+    assert_eq!(timestep.accumulate(0.04), 0);
+    assert_eq!(timestep.accumulate(0.04), 0);
+    // The time of the short frames adds up to a tick
+    assert_eq!(timestep.accumulate(0.04), 1);
+}
+
+#[test]
+fn long_frame_is_capped_and_its_leftover_time_is_dropped() {
+    let mut timestep = timestep();
+    assert_eq!(timestep.accumulate(2.), 5);
+    assert_eq!(timestep.accumulator, 0.);
+    assert_eq!(timestep.alpha(), 0.);
+    // The next frame is not slowed down by the time that was dropped
+    assert_eq!(timestep.accumulate(0.1), 1);
+}
+
+#[test]
+fn alpha_is_the_leftover_fraction_of_a_step() {
+    let mut timestep = timestep();
+    assert_eq!(timestep.accumulate(0.125), 1);
+    assert!((timestep.alpha() - 0.25).abs() < 1e-4);
+
+    // Each frame leaves the given fraction of a step for the next one
+    let frames = [
+        (0.013, 0.38),
+        (0.07, 0.08),
+        (0.1, 0.08),
+        (0.29, 0.98),
+        (0.333, 0.31),
+        (0.05, 0.81),
+    ];
+    for (delta_seconds, leftover) in frames.iter() {
+        timestep.accumulate(*delta_seconds);
+        let alpha = timestep.alpha();
+        assert!(
+            (0. ..1.).contains(&alpha),
+            "Alpha {} is out of range",
+            alpha
+        );
+        assert!((alpha - leftover).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn paused_timestep_runs_no_tick_and_forgets_its_time() {
+    let mut timestep = timestep();
+    timestep.accumulate(0.05);
+    timestep.paused = true;
+    assert_eq!(timestep.accumulate(1.), 0);
+    assert_eq!(timestep.alpha(), 0.);
+}